@@ -0,0 +1,514 @@
+#![warn(missing_docs)]
+#![warn(clippy::std_instead_of_core)]
+#![warn(clippy::std_instead_of_alloc)]
+#![forbid(unsafe_code)]
+// #![doc = include_str!("../README.md")]
+
+//! # Crustrace attributes
+//!
+//! The procedural macro attributes behind [`crustrace`](https://docs.rs/crustrace).
+//!
+//! Use them through the `crustrace` crate, which re-exports `tracing` for the generated code
+//! to refer to.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+
+/// Instruments a function to create and enter a `tracing` span every time
+/// the function is called.
+///
+/// Unless overridden, a span with `info` level will be generated.
+/// The generated span's name will be the name of the function.
+/// By default, all arguments to the function are included as fields on the span.
+///
+/// For `async fn`, the span is attached to the function's future with
+/// `tracing::Instrument`, so it is entered each time the future is polled and exited
+/// whenever it yields, rather than being held across `.await` points.
+/// The same goes for a function whose body ends in `Box::pin(async move { ... })`, as
+/// `#[async_trait]` desugars an `async fn` to: the async block is instrumented before it is
+/// boxed, so `#[instrument]` and `#[omni]` work both above and below `#[async_trait]`.
+///
+/// # Examples
+///
+/// Instrumenting a function:
+/// ```
+/// # use crustrace::instrument;
+/// #[instrument]
+/// pub fn my_function(my_arg: usize) {
+///     // This creates a span named `my_function` with field `my_arg`
+///     println!("inside my_function!");
+/// }
+/// ```
+///
+/// Setting the level for the generated span:
+/// ```
+/// # use crustrace::instrument;
+/// #[instrument(level = "debug")]
+/// pub fn my_function() {
+///     // Creates a DEBUG level span
+/// }
+/// ```
+///
+/// The level may be given as a string in any case, an integer from 1 (`trace`) to 5 (`error`),
+/// a bare identifier, or a `Level` path. Anything else is a compile error:
+/// ```
+/// # use crustrace::instrument;
+/// #[instrument(level = 2)]
+/// pub fn by_number() {}
+///
+/// #[instrument(level = tracing::Level::WARN, ret(level = trace))]
+/// pub fn by_path() -> u32 { 1 }
+/// ```
+///
+/// Overriding the generated span's name:
+/// ```
+/// # use crustrace::instrument;
+/// #[instrument(name = "my_custom_name")]
+/// pub fn my_function() {
+///     // Creates a span named `my_custom_name`
+/// }
+/// ```
+///
+/// Skipping parameters that shouldn't (or can't) be recorded, either by name or all at once:
+/// ```
+/// # use crustrace::instrument;
+/// #[instrument(skip(buf))]
+/// pub fn fill(buf: &mut Vec<u8>, len: usize) {
+///     // Creates a span with field `len` only
+///     buf.resize(len, 0);
+/// }
+///
+/// #[instrument(skip_all)]
+/// pub fn login(user: &str, password: &str) {
+///     // Creates a span with no fields
+/// }
+/// ```
+///
+/// Naming a parameter that doesn't exist in `skip(...)` is a compile error.
+///
+/// Parameters are recorded according to their type: integers, floats, `bool`, `str` and
+/// `String` (behind any references) as `tracing` values. Any other type is recorded as a value
+/// if it implements `tracing::Value`, otherwise with `Debug`, and otherwise as its type name,
+/// so no parameter type stops a function from being instrumented.
+/// A `#[record(Value | Debug | Display)]` attribute on a parameter overrides this:
+/// ```
+/// # use crustrace::instrument;
+/// # use std::path::PathBuf;
+/// #[instrument]
+/// pub fn copy(#[record(Debug)] len: usize, from: PathBuf, #[record(Display)] to: String) {
+///     // Creates a span with fields `len = ?len`, `from` (with `Debug`) and `to = %to`
+/// }
+/// ```
+///
+/// Adding custom fields to the span. Values may be any expression, and `?`/`%` record them
+/// with `Debug`/`Display`. A name without a value declares an empty field to be recorded
+/// later, and a field with the same name as a parameter replaces it:
+/// ```
+/// # use crustrace::instrument;
+/// #[derive(Debug)]
+/// pub struct User { pub id: u64 }
+///
+/// #[instrument(fields(user_id = user.id, %path, otel.kind = "server", status))]
+/// pub fn handle(user: &User, path: &str) {
+///     crustrace::tracing::Span::current().record("status", 200);
+/// }
+/// ```
+///
+/// Linking the span to the spans that caused it, without making it their child.
+/// `follows_from` takes an iterator of values convertible to `Option<tracing::Id>`:
+/// ```
+/// # use crustrace::instrument;
+/// # use crustrace::tracing::Id;
+/// #[instrument(follows_from = causes, skip(causes))]
+/// pub fn run_job(causes: Vec<Option<Id>>) {
+///     // The span follows from each of the (non-`None`) causes
+/// }
+/// ```
+///
+/// Recording the error when a function returns `Err` (at `error` level, with
+/// `Display` formatting, unless overridden with `err(level = "...")` or `err(Debug)`):
+/// ```
+/// # use crustrace::instrument;
+/// #[instrument(ret, err)]
+/// pub fn my_function(input: &str) -> Result<u32, core::num::ParseIntError> {
+///     // `Ok` values are recorded by the `ret` event, `Err` values by the `err` event
+///     input.parse()
+/// }
+/// ```
+///
+/// Layers that only look at spans don't see events, so `ret(record)` records the return value
+/// on the span itself, as a `return_value` field that is empty until the function returns:
+/// ```
+/// # use crustrace::instrument;
+/// #[instrument(ret(record))]
+/// pub fn my_function(x: u32) -> u32 {
+///     // The span gets `return_value = 2` when `my_function(1)` returns, and no event is emitted
+///     x * 2
+/// }
+/// ```
+///
+/// For subscribers that only log events, `events(enter, exit)` emits an `"enter <name>"` event
+/// with the parameters and an `"exit <name>"` event, and `timing` records how long the function
+/// took as the span's `elapsed_us` field (and on the exit event). Both happen when a guard is
+/// dropped, so they also cover early returns, `?` and panics:
+/// ```
+/// # use crustrace::instrument;
+/// #[instrument(events(enter, exit), timing)]
+/// pub fn my_function(input: &str) -> Result<u32, core::num::ParseIntError> {
+///     // "exit my_function" is emitted with `elapsed_us` whether this returns `Ok` or `Err`
+///     let value: u32 = input.parse()?;
+///     Ok(value * 2)
+/// }
+/// ```
+///
+/// Creating a span isn't `const`, so applying `#[instrument]` to a `const fn` is a compile error
+/// unless `const_twin = <name>` is given. That instruments a non-const version of the function,
+/// and keeps the original, uninstrumented, as a `const fn` named `<name>` for const contexts:
+/// ```
+/// # use crustrace::instrument;
+/// #[instrument(const_twin = double_const)]
+/// pub const fn double(x: u32) -> u32 {
+///     // `double` creates a span, `double_const` doesn't
+///     x * 2
+/// }
+///
+/// const DOUBLED: u32 = double_const(21);
+/// ```
+///
+/// A function that does synchronous set-up before returning a future can be given `future`,
+/// for a return type written as `impl Future<...>` or `Pin<Box<dyn Future<...>>>`. The span
+/// is entered while the body runs and then attached to the returned future, so it covers both
+/// the set-up and each poll, and `ret` and `err` record the future's output:
+/// ```
+/// # use crustrace::instrument;
+/// # use core::future::Future;
+/// # pub struct Client;
+/// # impl Client { async fn get(&self, url: String) -> u32 { 200 } }
+/// impl Client {
+///     #[instrument(future, skip(self))]
+///     pub fn fetch(&self, path: &str) -> impl Future<Output = u32> + '_ {
+///         let url = format!("https://example.com/{path}");
+///         self.get(url)
+///     }
+/// }
+/// ```
+///
+/// The generated code refers to `tracing` through `crustrace`'s re-export, so the calling
+/// crate doesn't need its own `tracing` dependency. To use a different path to the `tracing`
+/// crate, pass it as `crate = ...`, which all of the generated code then goes through, e.g. when
+/// `crustrace` is renamed or only `crustrace-attributes` is a dependency:
+/// ```
+/// # use crustrace::instrument;
+/// # mod my_reexports { pub use crustrace::tracing; }
+/// #[instrument(crate = my_reexports::tracing)]
+/// pub fn my_function() {}
+/// ```
+#[proc_macro_attribute]
+pub fn instrument(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args2: TokenStream2 = args.into();
+    let item2: TokenStream2 = item.into();
+
+    match crustrace_core::instrument_impl(args2, item2) {
+        Ok(tokens) => tokens.into(),
+        Err(error_tokens) => error_tokens.into(),
+    }
+}
+
+/// Instruments all functions within a module or impl block with tracing spans.
+///
+/// This macro applies the instrumentation behavior to every function found within
+/// the annotated module or impl block, automatically creating tracing spans for
+/// each function call. This provides a convenient way to add comprehensive tracing
+/// to an entire module without having to annotate each function individually.
+///
+/// The generated spans will use the default configuration (info level, function name
+/// as span name, and all function arguments as fields) unless arguments are given.
+/// `#[omni(...)]` accepts the same arguments as `#[instrument(...)]` and applies them to every
+/// function, except that `skip(...)` only needs to name parameters of some of them.
+///
+/// # Examples
+///
+/// Instrumenting all functions in a module:
+/// ```
+/// # use crustrace::omni;
+/// #[omni]
+/// mod my_module {
+///     pub fn function_one(x: i32) {
+///         // Automatically gets a span named `function_one` with field `x`
+///         println!("Function one called with {}", x);
+///     }
+///     
+///     pub fn function_two() {
+///         // Automatically gets a span named `function_two`
+///         println!("Function two called");
+///     }
+/// }
+/// ```
+///
+/// Instrumenting all methods in an impl block:
+/// ```
+/// # use crustrace::omni;
+/// struct MyStruct;
+///
+/// #[omni]
+/// impl MyStruct {
+///     pub fn method_one(&self, value: String) {
+///         // Automatically gets a span named `method_one` with field `value`
+///         println!("Method called with {}", value);
+///     }
+///     
+///     pub fn method_two(&self) {
+///         // Automatically gets a span named `method_two`
+///         println!("Another method called");
+///     }
+/// }
+/// ```
+///
+/// Instrumenting a whole module at `debug` level, recording return values:
+/// ```
+/// # use crustrace::omni;
+/// #[omni(level = "debug", ret, target = "db", skip(password))]
+/// mod db {
+///     pub fn connect(url: &str, password: &str) -> u32 {
+///         // Gets a DEBUG span with field `url`, and an event with the return value
+///         1
+///     }
+///
+///     pub fn ping() -> bool {
+///         true
+///     }
+/// }
+/// ```
+///
+/// As the module's arguments apply to functions returning anything, `err` is only applied to
+/// those returning a `Result`, and `ret` (unless given `Debug` or `Display`) records return
+/// values the way parameters are, by type name if they are neither a `tracing::Value` nor
+/// `Debug`.
+///
+/// Choosing which functions to instrument with `include` and `exclude` lists of glob
+/// patterns (`*` matches any run of characters, `?` any one character). A pattern matches a
+/// function by its name, or by its `Type::method` path in an impl block or trait. Functions
+/// that don't match an `include` pattern, if any are given, or that match an `exclude` pattern
+/// are left untouched:
+/// ```
+/// # use crustrace::omni;
+/// #[omni(include = ["handle_*", "Parser::*"], exclude = ["*_unchecked"])]
+/// mod server {
+///     pub struct Parser;
+///
+///     impl Parser {
+///         pub fn next(&self) {
+///             // Instrumented as `Parser::next` matches `Parser::*`
+///         }
+///     }
+///
+///     pub fn handle_request() {}
+///
+///     pub fn handle_unchecked() {
+///         // Not instrumented
+///     }
+/// }
+/// ```
+///
+/// Setting the level by visibility with `pub = ...`, `restricted = ...` (for `pub(crate)`,
+/// `pub(super)` and `pub(in path)`) and `private = ...`, which take precedence over `level`.
+/// Functions in traits and trait impls count as public. `only_pub` leaves everything but public
+/// functions untouched:
+/// ```
+/// # use crustrace::omni;
+/// #[omni(pub = "info", restricted = "debug", private = "trace")]
+/// mod api {
+///     pub fn handle() {
+///         // An INFO span
+///         helper();
+///     }
+///
+///     fn helper() {
+///         // A TRACE span
+///     }
+/// }
+///
+/// #[omni(only_pub)]
+/// mod quiet {
+///     pub fn handle() {
+///         // The only function instrumented
+///     }
+///
+///     pub(crate) fn helper() {}
+/// }
+/// ```
+///
+/// Impls of the standard formatting (`Debug`, `Display` and the other `fmt` traits), comparison
+/// (`PartialEq`, `Eq`, `PartialOrd`, `Ord`), hashing (`Hash`, `Hasher`, `BuildHasher`), `Clone`
+/// and `Drop` traits are left untouched, as spans on them are noise at best, and a span on
+/// `Debug::fmt` recurses whenever the type is recorded. Traits are matched by name, and
+/// `instrument_trait_impls = [...]` opts them back in with glob patterns:
+/// ```
+/// # use crustrace::omni;
+/// #[omni(instrument_trait_impls = ["Drop"])]
+/// mod connection {
+///     pub struct Connection;
+///
+///     impl core::fmt::Display for Connection {
+///         fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+///             // Not instrumented
+///             f.write_str("connection")
+///         }
+///     }
+///
+///     impl Drop for Connection {
+///         fn drop(&mut self) {
+///             // Gets a span named `drop`
+///         }
+///     }
+/// }
+/// ```
+///
+/// Methods in impl blocks and traits record their path as a `code.function.name` field, e.g.
+/// `Calculator::add`, or `<Calculator as Reset>::reset` in a trait impl. Their spans are named
+/// by the method name alone unless `span_names = "type"` names them `Calculator::add` and
+/// `Calculator::reset`, or `span_names = "qualified"` uses the full path:
+/// ```
+/// # use crustrace::omni;
+/// #[omni(span_names = "type")]
+/// mod calculator {
+///     pub struct Calculator;
+///
+///     impl Calculator {
+///         pub fn new() -> Self {
+///             // Gets a span named `Calculator::new`
+///             Calculator
+///         }
+///     }
+/// }
+/// ```
+///
+/// Spans are targeted by `module_path!()` by default, which includes any modules nested inside
+/// the annotated one. `module_names` prefixes span names with the path from the annotated
+/// module, and `module_targets` appends it to a `target` given to `#[omni]` (which it
+/// requires), so that filter directives can still pick out nested modules:
+/// ```
+/// # use crustrace::omni;
+/// #[omni(target = "app", module_names, module_targets)]
+/// mod app {
+///     pub mod db {
+///         pub fn connect() {
+///             // Gets a span named `db::connect` with target `app::db`
+///         }
+///     }
+/// }
+/// ```
+///
+/// Functions, impl blocks and modules declared inside function bodies are left alone unless
+/// `deep` is given, which instruments those declared at the top level of the body of each
+/// instrumented function. Closures, calls and items inside nested blocks are never instrumented:
+/// ```
+/// # use crustrace::omni;
+/// #[omni(deep)]
+/// mod my_module {
+///     pub fn outer(x: u32) -> u32 {
+///         fn helper(y: u32) -> u32 {
+///             // Gets a span named `helper`
+///             y * 2
+///         }
+///
+///         let add_one = |y: u32| y + 1;
+///         add_one(helper(x))
+///     }
+/// }
+/// ```
+///
+/// `const fn`s are left untouched, as they can't create spans.
+///
+/// Items marked `#[cfg(test)]` (or with `test` inside a `cfg(all(...))` or `cfg(any(...))`)
+/// and functions marked `#[test]`, `#[bench]` or another crate's test attribute (like
+/// `#[tokio::test]`) are left untouched, along with everything inside them, unless
+/// `include_tests` is given:
+/// ```
+/// # use crustrace::omni;
+/// #[omni]
+/// mod math {
+///     pub fn add(a: u32, b: u32) -> u32 {
+///         // Instrumented
+///         a + b
+///     }
+///
+///     #[cfg(test)]
+///     mod tests {
+///         pub fn fixture() -> u32 {
+///             // Not instrumented, nor are the tests
+///             2
+///         }
+///     }
+/// }
+/// ```
+///
+/// As with `#[instrument]`, `crate = ...` sets the path to the `tracing` crate used by
+/// every instrumented function:
+/// ```
+/// # use crustrace::omni;
+/// #[omni(crate = ::crustrace::tracing)]
+/// mod my_module {
+///     pub fn function_one(x: i32) {}
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn omni(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args2: TokenStream2 = args.into();
+    let input2: TokenStream2 = input.into();
+
+    match crustrace_core::trace_all_impl(args2, input2) {
+        Ok(tokens) => tokens.into(),
+        Err(error_tokens) => error_tokens.into(),
+    }
+}
+
+/// Excludes an item from `#[omni]` instrumentation.
+///
+/// Inside an `#[omni]` module, a function, impl block, module or trait marked
+/// `#[crustrace::skip]` is left exactly as written, along with everything inside it.
+/// Outside of `#[omni]` it has no effect.
+///
+/// # Examples
+///
+/// ```
+/// # use crustrace::omni;
+/// #[omni]
+/// mod my_module {
+///     pub fn traced(x: i32) -> i32 {
+///         hot_loop(x)
+///     }
+///
+///     #[crustrace::skip]
+///     pub fn hot_loop(x: i32) -> i32 {
+///         // No span is created for this function
+///         x * 2
+///     }
+/// }
+/// ```
+///
+/// A function's own `#[instrument(...)]` attribute inside `#[omni]` overrides the module's
+/// arguments, argument by argument, rather than instrumenting the function twice:
+/// ```
+/// # use crustrace::omni;
+/// #[omni(level = "debug", ret)]
+/// mod my_module {
+///     #[crustrace::instrument(level = "info")]
+///     pub fn entry_point(x: i32) -> i32 {
+///         // An INFO span, still recording the return value
+///         x + 1
+///     }
+/// }
+/// ```
+///
+/// A function with `#[tracing::instrument]` is left to it, and isn't instrumented by `#[omni]`.
+#[proc_macro_attribute]
+pub fn skip(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args2: TokenStream2 = args.into();
+    let item2: TokenStream2 = item.into();
+
+    match crustrace_core::skip_impl(args2, item2) {
+        Ok(tokens) => tokens.into(),
+        Err(error_tokens) => error_tokens.into(),
+    }
+}
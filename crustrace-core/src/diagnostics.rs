@@ -0,0 +1,61 @@
+//! Spanned compile errors for macro input that can't be instrumented
+//!
+//! Errors carry the span of the offending token, so `compile_error!` is reported on that token
+//! rather than on the whole attribute.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote_spanned;
+use unsynn::ErrorKind;
+
+/// A compile error message attached to a source span
+#[derive(Debug)]
+pub(crate) struct Diagnostic {
+    span: Span,
+    message: String,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// Report `message` on the token an `unsynn` parse failed at, or on `fallback` if the
+    /// error doesn't point at a token (e.g. the input ended early)
+    pub(crate) fn from_parse_error(
+        error: &unsynn::Error,
+        fallback: Span,
+        message: impl Into<String>,
+    ) -> Self {
+        let span = match &error.kind {
+            ErrorKind::UnexpectedToken { at, .. } => at.clone().next().map(|token| token.span()),
+            _ => None,
+        };
+        Self::new(span.unwrap_or(fallback), message)
+    }
+
+    /// The `compile_error!` invocation reporting this diagnostic
+    pub(crate) fn into_compile_error(self) -> TokenStream {
+        let message = self.message;
+        quote_spanned! {self.span=>
+            compile_error!(#message);
+        }
+    }
+}
+
+impl core::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Span of the first token in `tokens`, or the call site if there are none
+pub(crate) fn first_span(tokens: &TokenStream) -> Span {
+    tokens
+        .clone()
+        .into_iter()
+        .next()
+        .map_or_else(Span::call_site, |token| token.span())
+}
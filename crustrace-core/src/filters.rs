@@ -0,0 +1,286 @@
+//! Filters and policies choosing which functions `#[omni]` instruments, at what level, and
+//! what their spans are named
+
+use proc_macro2::TokenStream;
+
+use crate::tracer::FunctionNaming;
+
+/// `include = [...]` and `exclude = [...]` glob patterns, matched against a function's name and
+/// its `Type::method` path
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NameFilters {
+    /// If any are given, only functions matching one of these are instrumented
+    pub(crate) include: Vec<String>,
+    /// Functions matching any of these are never instrumented
+    pub(crate) exclude: Vec<String>,
+}
+
+impl NameFilters {
+    /// Whether the function `name`, defined in the impl block or trait `owner` if any, should be
+    /// instrumented
+    pub(crate) fn allows(&self, name: &str, owner: Option<&str>) -> bool {
+        let path = owner.map(|owner| format!("{owner}::{name}"));
+        let matches = |pattern: &String| {
+            glob_match(pattern, name) || path.as_ref().is_some_and(|path| glob_match(pattern, path))
+        };
+        (self.include.is_empty() || self.include.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+}
+
+/// Declared visibility of a function
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FnVisibility {
+    /// `pub`, or a function in a trait or trait impl
+    Public,
+    /// `pub(crate)`, `pub(super)` or `pub(in path)`
+    Restricted,
+    /// No visibility
+    Private,
+}
+
+/// `pub = ...`, `restricted = ...` and `private = ...` levels, and `only_pub`
+#[derive(Clone, Debug, Default)]
+pub(crate) struct VisibilityPolicy {
+    /// `level = ...` instrument arguments for public functions
+    pub(crate) public: Option<TokenStream>,
+    /// `level = ...` instrument arguments for functions with restricted visibility
+    pub(crate) restricted: Option<TokenStream>,
+    /// `level = ...` instrument arguments for private functions
+    pub(crate) private: Option<TokenStream>,
+    /// Only instrument public functions
+    pub(crate) only_pub: bool,
+}
+
+impl VisibilityPolicy {
+    /// Whether a function with this visibility should be instrumented
+    pub(crate) fn allows(&self, visibility: FnVisibility) -> bool {
+        !self.only_pub || visibility == FnVisibility::Public
+    }
+
+    /// The instrument arguments setting the level of functions with this visibility, if any
+    pub(crate) fn level_args(&self, visibility: FnVisibility) -> Option<&TokenStream> {
+        match visibility {
+            FnVisibility::Public => self.public.as_ref(),
+            FnVisibility::Restricted => self.restricted.as_ref(),
+            FnVisibility::Private => self.private.as_ref(),
+        }
+    }
+}
+
+/// Standard formatting, comparison, hashing, cloning and drop traits, whose impls `#[omni]`
+/// leaves uninstrumented unless named in `instrument_trait_impls = [...]`.
+///
+/// Spans on these are mostly noise, and on `Debug` and `Display` they recurse whenever the
+/// implementing type is recorded as a field.
+pub(crate) const SKIPPED_TRAIT_IMPLS: &[&str] = &[
+    "Debug",
+    "Display",
+    "Binary",
+    "Octal",
+    "LowerHex",
+    "UpperHex",
+    "LowerExp",
+    "UpperExp",
+    "Pointer",
+    "PartialEq",
+    "Eq",
+    "PartialOrd",
+    "Ord",
+    "Hash",
+    "Hasher",
+    "BuildHasher",
+    "Clone",
+    "Drop",
+];
+
+/// `instrument_trait_impls = [...]` glob patterns, opting impls of the traits in
+/// [`SKIPPED_TRAIT_IMPLS`] back in
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TraitImplFilter {
+    /// Trait names to instrument impls of despite being skipped by default
+    pub(crate) instrument: Vec<String>,
+}
+
+impl TraitImplFilter {
+    /// Whether the functions in an impl of the trait `name` should be instrumented
+    pub(crate) fn allows(&self, name: &str) -> bool {
+        !SKIPPED_TRAIT_IMPLS.contains(&name)
+            || self
+                .instrument
+                .iter()
+                .any(|pattern| glob_match(pattern, name))
+    }
+}
+
+/// `span_names = ...`: how the spans of methods in impl blocks and traits are named
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum SpanNames {
+    /// `"short"`: the method name alone, as `#[instrument]` names it
+    #[default]
+    Short,
+    /// `"type"`: `Type::method`, or `Trait::method` in a trait
+    Type,
+    /// `"qualified"`: `<Type as Trait>::method` in a trait impl, otherwise as for `Type`
+    Qualified,
+}
+
+impl SpanNames {
+    /// The naming given as `span_names = "..."`, if it is one of the known ones
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "short" => Some(Self::Short),
+            "type" => Some(Self::Type),
+            "qualified" => Some(Self::Qualified),
+            _ => None,
+        }
+    }
+
+    /// The span name and qualified path of the method `name` in the impl block or trait for
+    /// `owner`, implementing `impl_trait` if it is a trait impl
+    pub(crate) fn method_name(
+        self,
+        name: &str,
+        owner: &str,
+        impl_trait: Option<&str>,
+    ) -> FunctionNaming {
+        let type_path = format!("{owner}::{name}");
+        let qualified = match impl_trait {
+            Some(impl_trait) => format!("<{owner} as {impl_trait}>::{name}"),
+            None => type_path.clone(),
+        };
+        let span_name = match self {
+            Self::Short => name.to_string(),
+            Self::Type => type_path,
+            Self::Qualified => qualified.clone(),
+        };
+        FunctionNaming {
+            span_name: Some(span_name),
+            qualified: Some(qualified),
+            target_suffix: None,
+        }
+    }
+}
+
+/// `module_names` and `module_targets`: whether functions in modules nested inside the one
+/// `#[omni]` is applied to are named and targeted by the path to them
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ModulePaths {
+    /// Prefix span names with the nested module path, e.g. `b::f`
+    pub(crate) names: bool,
+    /// Append the nested module path to the `target` given to `#[omni]`
+    pub(crate) targets: bool,
+}
+
+/// Match `text` against a glob `pattern`, where `*` matches any run of characters and `?` any
+/// single character
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    // Backtrack to just after the last `*`, consuming one more character with it each time
+    let (mut p, mut t) = (0, 0);
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("handle_*", "handle_request"));
+        assert!(glob_match("*_unchecked", "get_unchecked"));
+        assert!(glob_match("fmt", "fmt"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("p?rse", "parse"));
+        assert!(glob_match("Widget::*", "Widget::new"));
+        assert!(glob_match("*a*b*", "xaybz"));
+        assert!(!glob_match("handle_*", "handler"));
+        assert!(!glob_match("fmt", "fmt_all"));
+        assert!(!glob_match("*_unchecked", "unchecked"));
+    }
+
+    #[test]
+    fn test_filters_on_name_and_path() {
+        let filters = NameFilters {
+            include: vec!["handle_*".to_string(), "Parser::*".to_string()],
+            exclude: vec!["*_unchecked".to_string(), "fmt".to_string()],
+        };
+
+        assert!(filters.allows("handle_get", None));
+        assert!(filters.allows("next", Some("Parser")));
+        assert!(!filters.allows("next", Some("Lexer")));
+        assert!(!filters.allows("handle_unchecked", None));
+        assert!(!filters.allows("fmt", Some("Parser")));
+        assert!(NameFilters::default().allows("anything", None));
+    }
+
+    #[test]
+    fn test_span_names() {
+        let names = |naming: SpanNames, impl_trait| {
+            let method = naming.method_name("fmt", "Widget", impl_trait);
+            (method.span_name.unwrap(), method.qualified.unwrap())
+        };
+
+        assert_eq!(
+            names(SpanNames::Short, Some("Display")),
+            ("fmt".to_string(), "<Widget as Display>::fmt".to_string())
+        );
+        assert_eq!(
+            names(SpanNames::Type, Some("Display")),
+            (
+                "Widget::fmt".to_string(),
+                "<Widget as Display>::fmt".to_string()
+            )
+        );
+        assert_eq!(
+            names(SpanNames::Qualified, Some("Display")),
+            (
+                "<Widget as Display>::fmt".to_string(),
+                "<Widget as Display>::fmt".to_string()
+            )
+        );
+        assert_eq!(
+            names(SpanNames::Qualified, None),
+            ("Widget::fmt".to_string(), "Widget::fmt".to_string())
+        );
+    }
+
+    #[test]
+    fn test_trait_impl_filter() {
+        let defaults = TraitImplFilter::default();
+        assert!(!defaults.allows("Debug"));
+        assert!(!defaults.allows("Drop"));
+        assert!(defaults.allows("Iterator"));
+        assert!(defaults.allows("Render"));
+
+        let filter = TraitImplFilter {
+            instrument: vec!["Drop".to_string(), "Partial*".to_string()],
+        };
+        assert!(filter.allows("Drop"));
+        assert!(filter.allows("PartialOrd"));
+        assert!(!filter.allows("Clone"));
+    }
+}
@@ -1,4 +1,6 @@
 /// Crustrace: tracing instrumentation helper macros
+mod diagnostics;
+mod filters;
 mod parse;
 mod token_processors;
 
@@ -6,4 +8,4 @@ mod tracer;
 pub use tracer::instrument_impl;
 
 mod omnibus;
-pub use omnibus::trace_all_impl;
+pub use omnibus::{skip_impl, trace_all_impl};
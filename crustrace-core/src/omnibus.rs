@@ -1,7 +1,251 @@
+use core::result::Result;
 use proc_macro2::TokenStream;
+use quote::quote;
+use unsynn::*;
 
+use crate::diagnostics::{first_span, Diagnostic};
+use crate::filters::{ModulePaths, NameFilters, SpanNames, TraitImplFilter, VisibilityPolicy};
+use crate::parse::{InstrumentArg, OmniArg, VerbatimUntil, VisibilityKind};
 use crate::token_processors::TokenProcessor;
+use crate::tracer::{argument_usage, parse_arg, parse_instrument_args, resolve_level};
 
-pub fn trace_all_impl(input: TokenStream) -> TokenStream {
-    TokenProcessor::new(input).process()
+/// Arguments accepted by `#[omni(...)]`, listed in error messages
+const OMNI_ARGS: &str = "level, name, target, parent, follows_from, ret, err, skip, skip_all, \
+                         fields, crate, events, timing, include, exclude, pub, restricted, \
+                         private, only_pub, instrument_trait_impls, span_names, module_names, \
+                         module_targets, deep, include_tests";
+
+/// The expected form of each omni argument, for errors on malformed arguments
+fn omni_argument_usage(name: &str) -> Option<&'static str> {
+    match name {
+        "include" => Some("`include = [\"pattern\", ...]`"),
+        "exclude" => Some("`exclude = [\"pattern\", ...]`"),
+        "pub" => Some("`pub = \"info\"`, `pub = 1..=5` or `pub = Level::INFO`"),
+        "restricted" => {
+            Some("`restricted = \"debug\"`, `restricted = 1..=5` or `restricted = Level::DEBUG`")
+        }
+        "private" => Some("`private = \"trace\"`, `private = 1..=5` or `private = Level::TRACE`"),
+        "only_pub" => Some("`only_pub`"),
+        "instrument_trait_impls" => Some("`instrument_trait_impls = [\"Trait\", ...]`"),
+        "module_names" => Some("`module_names`"),
+        "module_targets" => Some("`module_targets`"),
+        "deep" => Some("`deep`"),
+        "include_tests" => Some("`include_tests`"),
+        "span_names" => Some("`span_names = \"short\"`, `\"type\"` or `\"qualified\"`"),
+        _ => argument_usage(name),
+    }
+}
+
+/// Parsed `#[omni(...)]` arguments
+#[derive(Default)]
+struct OmniArgs {
+    /// Instrument arguments applied to every function
+    instrument: TokenStream,
+    /// Which functions to instrument
+    filters: NameFilters,
+    /// Levels and inclusion by visibility
+    policy: VisibilityPolicy,
+    /// Which trait impls to instrument
+    trait_impls: TraitImplFilter,
+    /// How methods' spans are named
+    span_names: SpanNames,
+    /// Whether nested module paths are added to span names and targets
+    module_paths: ModulePaths,
+    /// Whether to instrument items declared inside function bodies
+    deep: bool,
+    /// Whether to instrument test modules and functions
+    include_tests: bool,
+}
+
+pub fn trace_all_impl(args: TokenStream, input: TokenStream) -> Result<TokenStream, TokenStream> {
+    let omni_args = parse_omni_args(&args).map_err(Diagnostic::into_compile_error)?;
+    Ok(TokenProcessor::new(input)
+        .with_args(omni_args.instrument)
+        .with_filters(omni_args.filters)
+        .with_policy(omni_args.policy)
+        .with_trait_impls(omni_args.trait_impls)
+        .with_span_names(omni_args.span_names)
+        .with_module_paths(omni_args.module_paths)
+        .with_deep(omni_args.deep)
+        .with_include_tests(omni_args.include_tests)
+        .process())
+}
+
+/// `#[crustrace::skip]` only marks items for `#[omni]` to leave alone, so it expands to the
+/// item unchanged
+pub fn skip_impl(args: TokenStream, item: TokenStream) -> Result<TokenStream, TokenStream> {
+    if !args.is_empty() {
+        return Err(
+            Diagnostic::new(first_span(&args), "`#[crustrace::skip]` takes no arguments")
+                .into_compile_error(),
+        );
+    }
+    Ok(item)
+}
+
+/// Check the `#[omni(...)]` arguments, separating the instrument arguments to pass on to every
+/// instrumented function from those for `#[omni]` itself
+fn parse_omni_args(args: &TokenStream) -> Result<OmniArgs, Diagnostic> {
+    if args.is_empty() {
+        return Ok(OmniArgs::default());
+    }
+
+    let arg_list = args
+        .to_token_iter()
+        .parse_all::<CommaDelimitedVec<VerbatimUntil<Comma>>>()
+        .map_err(|e| {
+            Diagnostic::from_parse_error(
+                &e,
+                first_span(args),
+                "expected comma-separated omni arguments",
+            )
+        })?;
+
+    let mut instrument_args = Vec::new();
+    let mut include = None;
+    let mut exclude = None;
+    let mut trait_impls = None;
+    let mut span_names = None;
+    let mut module_paths = ModulePaths::default();
+    let mut module_targets_span = None;
+    let mut has_target = false;
+    let mut deep = false;
+    let mut include_tests = false;
+    let mut policy = VisibilityPolicy::default();
+    for arg_tokens in arg_list.0 {
+        let arg_tokens = arg_tokens.value.to_token_stream();
+        let span = first_span(&arg_tokens);
+        match parse_arg::<OmniArg>(&arg_tokens, "omni", OMNI_ARGS, omni_argument_usage)? {
+            OmniArg::Include(include_arg) => {
+                if include.is_some() {
+                    return Err(Diagnostic::new(
+                        span,
+                        "expected only a single `include` argument",
+                    ));
+                }
+                include = Some(patterns(include_arg.patterns.content));
+            }
+            OmniArg::Exclude(exclude_arg) => {
+                if exclude.is_some() {
+                    return Err(Diagnostic::new(
+                        span,
+                        "expected only a single `exclude` argument",
+                    ));
+                }
+                exclude = Some(patterns(exclude_arg.patterns.content));
+            }
+            OmniArg::VisibilityLevel(level_arg) => {
+                resolve_level(&level_arg.value)?;
+                let mut value = TokenStream::new();
+                unsynn::ToTokens::to_tokens(&level_arg.value, &mut value);
+                let (slot, name) = match level_arg.visibility {
+                    VisibilityKind::Public(_) => (&mut policy.public, "pub"),
+                    VisibilityKind::Restricted(_) => (&mut policy.restricted, "restricted"),
+                    VisibilityKind::Private(_) => (&mut policy.private, "private"),
+                };
+                if slot.is_some() {
+                    return Err(Diagnostic::new(
+                        span,
+                        format!("expected only a single `{}` argument", name),
+                    ));
+                }
+                *slot = Some(quote!(level = #value));
+            }
+            OmniArg::OnlyPub(_) => policy.only_pub = true,
+            OmniArg::ModuleNames(_) => module_paths.names = true,
+            OmniArg::ModuleTargets(_) => {
+                module_paths.targets = true;
+                module_targets_span = Some(span);
+            }
+            OmniArg::Deep(_) => deep = true,
+            OmniArg::IncludeTests(_) => include_tests = true,
+            OmniArg::InstrumentTraitImpls(trait_impls_arg) => {
+                if trait_impls.is_some() {
+                    return Err(Diagnostic::new(
+                        span,
+                        "expected only a single `instrument_trait_impls` argument",
+                    ));
+                }
+                trait_impls = Some(patterns(trait_impls_arg.traits.content));
+            }
+            OmniArg::SpanNames(span_names_arg) => {
+                if span_names.is_some() {
+                    return Err(Diagnostic::new(
+                        span,
+                        "expected only a single `span_names` argument",
+                    ));
+                }
+                let naming = span_names_arg.value.as_str();
+                span_names = Some(SpanNames::from_name(naming).ok_or_else(|| {
+                    Diagnostic::new(
+                        first_span(&span_names_arg.value.to_token_stream()),
+                        format!(
+                            "unknown span naming `{}`, expected \"short\", \"type\" or \"qualified\"",
+                            naming
+                        ),
+                    )
+                })?);
+            }
+            // `const fn`s are never instrumented by `#[omni]`, and each twin needs its own name
+            OmniArg::Instrument(InstrumentArg::ConstTwin(_)) => {
+                return Err(Diagnostic::new(
+                    span,
+                    "`const_twin` can only be given to `#[instrument]` on a `const fn`",
+                ));
+            }
+            // Most functions don't return a future, so `future` is given function by function
+            OmniArg::Instrument(InstrumentArg::Future(_)) => {
+                return Err(Diagnostic::new(
+                    span,
+                    "`future` can only be given to `#[instrument]` on a function returning a \
+                     future",
+                ));
+            }
+            OmniArg::Instrument(instrument_arg) => {
+                has_target |= matches!(instrument_arg, InstrumentArg::Target(_));
+                instrument_args.push(arg_tokens);
+            }
+        }
+    }
+
+    // Without a `target`, spans have the `module_path!()` target, which has the nested modules
+    // in it already
+    if let (Some(span), false) = (module_targets_span, has_target) {
+        return Err(Diagnostic::new(
+            span,
+            "`module_targets` requires a `target`, as the default `module_path!()` target \
+             already includes nested modules",
+        ));
+    }
+
+    // Duplicate and conflicting instrument arguments are reported once, on the module
+    let instrument_args = quote!(#(#instrument_args),*);
+    parse_instrument_args(&mut instrument_args.to_token_iter())?;
+
+    Ok(OmniArgs {
+        instrument: instrument_args,
+        filters: NameFilters {
+            include: include.unwrap_or_default(),
+            exclude: exclude.unwrap_or_default(),
+        },
+        policy,
+        trait_impls: TraitImplFilter {
+            instrument: trait_impls.unwrap_or_default(),
+        },
+        span_names: span_names.unwrap_or_default(),
+        module_paths,
+        deep,
+        include_tests,
+    })
+}
+
+/// The glob patterns in an `include`, `exclude` or `instrument_trait_impls` list
+fn patterns(list: Option<CommaDelimitedVec<LiteralString>>) -> Vec<String> {
+    list.map(|list| {
+        list.0
+            .into_iter()
+            .map(|pattern| pattern.value.as_str().to_string())
+            .collect()
+    })
+    .unwrap_or_default()
 }
@@ -40,8 +40,18 @@ keyword! {
     pub KSelf = "self";
     /// The "mut" keyword
     pub KMut = "mut";
+    /// The "move" keyword
+    pub KMove = "move";
     /// The "ret" keyword (in the tracing macro)
     pub KRet = "ret";
+    /// The "err" keyword (in the tracing macro)
+    pub KErr = "err";
+    /// The "skip" keyword (in the tracing macro skip arg)
+    pub KSkip = "skip";
+    /// The "skip_all" keyword (in the tracing macro)
+    pub KSkipAll = "skip_all";
+    /// The "fields" keyword (in the tracing macro fields arg)
+    pub KFields = "fields";
     /// The "Debug" keyword (in the tracing macro ret arg)
     pub KDebug = "Debug";
     /// The "Display" keyword (in the tracing macro ret arg)
@@ -50,6 +60,48 @@ keyword! {
     pub KTarget = "target";
     /// The "parent" keyword (in the tracing macro target arg)
     pub KParent = "parent";
+    /// The "follows_from" keyword (in the tracing macro follows_from arg)
+    pub KFollowsFrom = "follows_from";
+    /// The "record" keyword (in parameter attributes)
+    pub KRecord = "record";
+    /// The "Value" keyword (in the record parameter attribute)
+    pub KValue = "Value";
+    /// The "const_twin" keyword (in the tracing macro const_twin arg)
+    pub KConstTwin = "const_twin";
+    /// The "future" keyword (in the tracing macro future arg)
+    pub KFuture = "future";
+    /// The "events" keyword (in the tracing macro events arg)
+    pub KEvents = "events";
+    /// The "enter" keyword (in the tracing macro events arg)
+    pub KEnter = "enter";
+    /// The "exit" keyword (in the tracing macro events arg)
+    pub KExit = "exit";
+    /// The "timing" keyword (in the tracing macro timing arg)
+    pub KTiming = "timing";
+    /// The "crustrace" keyword (in attribute paths)
+    pub KCrustrace = "crustrace";
+    /// The "include" keyword (in the omni macro)
+    pub KInclude = "include";
+    /// The "exclude" keyword (in the omni macro)
+    pub KExclude = "exclude";
+    /// The "restricted" keyword (in the omni macro)
+    pub KRestricted = "restricted";
+    /// The "private" keyword (in the omni macro)
+    pub KPrivate = "private";
+    /// The "only_pub" keyword (in the omni macro)
+    pub KOnlyPub = "only_pub";
+    /// The "instrument_trait_impls" keyword (in the omni macro)
+    pub KInstrumentTraitImpls = "instrument_trait_impls";
+    /// The "span_names" keyword (in the omni macro)
+    pub KSpanNames = "span_names";
+    /// The "module_names" keyword (in the omni macro)
+    pub KModuleNames = "module_names";
+    /// The "module_targets" keyword (in the omni macro)
+    pub KModuleTargets = "module_targets";
+    /// The "deep" keyword (in the omni macro)
+    pub KDeep = "deep";
+    /// The "include_tests" keyword (in the omni macro)
+    pub KIncludeTests = "include_tests";
 }
 
 operator! {
@@ -82,15 +134,53 @@ unsynn! {
         Target(TargetArg),
         /// parent = "some_span"
         Parent(ParentArg),
+        /// follows_from = [cause_id]
+        FollowsFrom(FollowsFromArg),
         /// ret
         Ret(RetArgs),
+        /// err
+        Err(ErrArgs),
+        /// skip(a, b)
+        Skip(SkipArg),
+        /// skip_all
+        SkipAll(KSkipAll),
+        /// fields(key = value, ?dbg, %disp)
+        Fields(FieldsArg),
+        /// crate = path::to::tracing
+        Crate(CrateArg),
+        /// const_twin = name_const
+        ConstTwin(ConstTwinArg),
+        /// future
+        Future(KFuture),
+        /// events(enter, exit)
+        Events(EventsArg),
+        /// timing
+        Timing(KTiming),
     }
 
-    /// Level argument: level = "debug"
+    /// Level argument: level = "debug", level = 2 or level = Level::DEBUG
     pub struct LevelArg {
         pub _level: KLevel,
         pub _eq: Eq,
-        pub value: LiteralString,
+        pub value: LevelValue,
+    }
+
+    /// Value of a level argument, resolved by [`LevelValue::level`]
+    pub enum LevelValue {
+        /// "debug", in any case
+        Str(LiteralString),
+        /// 1 (trace) to 5 (error)
+        Int(LiteralInteger),
+        /// debug, DEBUG, Level::DEBUG or tracing::Level::DEBUG
+        Path(LevelPath),
+    }
+
+    /// Path naming a level, optionally qualified: ::tracing::Level::DEBUG
+    pub struct LevelPath {
+        /// Optional leading ::
+        pub _leading: Option<PathSep>,
+        /// Path segments
+        pub segments: PathSepDelimitedVec<Ident>,
     }
 
     /// Name argument: name = "custom"
@@ -114,6 +204,160 @@ unsynn! {
         pub value: VerbatimUntil<Comma>,  // Note: parent can be expressions, not just strings
     }
 
+    /// Single omni argument
+    pub enum OmniArg {
+        /// include = ["handle_*", "Parser::*"]
+        Include(IncludeArg),
+        /// exclude = ["*_unchecked", "fmt"]
+        Exclude(ExcludeArg),
+        /// pub = "info", restricted = "debug" or private = "trace"
+        VisibilityLevel(VisibilityLevelArg),
+        /// only_pub
+        OnlyPub(KOnlyPub),
+        /// instrument_trait_impls = ["Debug", "Clone"]
+        InstrumentTraitImpls(InstrumentTraitImplsArg),
+        /// span_names = "qualified"
+        SpanNames(SpanNamesArg),
+        /// module_names
+        ModuleNames(KModuleNames),
+        /// module_targets
+        ModuleTargets(KModuleTargets),
+        /// deep
+        Deep(KDeep),
+        /// include_tests
+        IncludeTests(KIncludeTests),
+        /// Any instrument argument, applied to every instrumented function
+        Instrument(InstrumentArg),
+    }
+
+    /// Level for functions of one visibility: pub = "info"
+    pub struct VisibilityLevelArg {
+        pub visibility: VisibilityKind,
+        pub _eq: Eq,
+        pub value: LevelValue,
+    }
+
+    /// Visibility named in an omni level policy
+    pub enum VisibilityKind {
+        /// pub
+        Public(KPub),
+        /// pub(crate), pub(super), pub(in path)
+        Restricted(KRestricted),
+        /// No visibility
+        Private(KPrivate),
+    }
+
+    /// Include argument: include = ["handle_*", "Parser::*"]
+    pub struct IncludeArg {
+        pub _include: KInclude,
+        pub _eq: Eq,
+        /// Glob patterns, matched against function names and `Type::method` paths
+        pub patterns: BracketGroupContaining<Option<CommaDelimitedVec<LiteralString>>>,
+    }
+
+    /// Exclude argument: exclude = ["*_unchecked", "fmt"]
+    pub struct ExcludeArg {
+        pub _exclude: KExclude,
+        pub _eq: Eq,
+        /// Glob patterns, matched against function names and `Type::method` paths
+        pub patterns: BracketGroupContaining<Option<CommaDelimitedVec<LiteralString>>>,
+    }
+
+    /// Trait impls to instrument despite omni skipping them by default:
+    /// instrument_trait_impls = ["Debug", "Clone"]
+    pub struct InstrumentTraitImplsArg {
+        pub _instrument_trait_impls: KInstrumentTraitImpls,
+        pub _eq: Eq,
+        /// Glob patterns, matched against trait names
+        pub traits: BracketGroupContaining<Option<CommaDelimitedVec<LiteralString>>>,
+    }
+
+    /// How methods' spans are named: span_names = "short", "type" or "qualified"
+    pub struct SpanNamesArg {
+        pub _span_names: KSpanNames,
+        pub _eq: Eq,
+        pub value: LiteralString,
+    }
+
+    /// Crate argument: crate = path::to::tracing
+    pub struct CrateArg {
+        pub _crate: KCrate,
+        pub _eq: Eq,
+        /// Path to the `tracing` crate used by the generated code
+        pub path: VerbatimUntil<Comma>,
+    }
+
+    /// Const twin argument: const_twin = name_const
+    pub struct ConstTwinArg {
+        pub _const_twin: KConstTwin,
+        pub _eq: Eq,
+        /// Name of the uninstrumented `const fn` kept alongside the instrumented one
+        pub name: Ident,
+    }
+
+    /// Events argument: events(enter, exit)
+    pub struct EventsArg {
+        pub _events: KEvents,
+        /// Which events to emit
+        pub kinds: ParenthesisGroupContaining<CommaDelimitedVec<SpanEvent>>,
+    }
+
+    /// An event emitted for a function's span, in an events(...) argument
+    pub enum SpanEvent {
+        /// On entry, with the parameters
+        Enter(KEnter),
+        /// On exit, however the function returns
+        Exit(KExit),
+    }
+
+    /// Follows-from argument: follows_from = [cause_id]
+    pub struct FollowsFromArg {
+        pub _follows_from: KFollowsFrom,
+        pub _eq: Eq,
+        pub value: VerbatimUntil<Comma>,  // An iterator of `impl Into<Option<Id>>` values
+    }
+
+    /// Skip argument: skip(a, b)
+    pub struct SkipArg {
+        pub _skip: KSkip,
+        /// Names of the parameters not to record
+        pub params: ParenthesisGroupContaining<Option<CommaDelimitedVec<Ident>>>,
+    }
+
+    /// Fields argument: fields(user_id = req.user.id, %path, otel.kind = "server")
+    pub struct FieldsArg {
+        pub _fields: KFields,
+        /// Custom span fields
+        pub fields: ParenthesisGroupContaining<Option<CommaDelimitedVec<FieldArg>>>,
+    }
+
+    /// Single custom span field: `name`, `?name`, `%name`, `a.b.c = expr` or `name = %expr`
+    pub struct FieldArg {
+        /// Optional sigil before the name (only meaningful without a value)
+        pub kind: Option<FieldKind>,
+        /// Field name, possibly dotted
+        pub name: DotDelimitedVec<Ident>,
+        /// Optional value expression
+        pub value: Option<FieldValue>,
+    }
+
+    /// Field value: = ?expr, = %expr or = expr
+    pub struct FieldValue {
+        pub _eq: Eq,
+        /// Optional sigil before the value expression
+        pub kind: Option<FieldKind>,
+        /// The value expression
+        pub expr: VerbatimUntil<Comma>,
+    }
+
+    /// How a custom field value is recorded
+    pub enum FieldKind {
+        /// Debug format (?)
+        Debug(Question),
+        /// Display format (%)
+        Display(Percent),
+    }
+
     /// Complete function signature
     pub struct FnSig {
         /// Optional attributes (#[...])
@@ -151,6 +395,43 @@ unsynn! {
         pub content: BracketGroup,
     }
 
+    /// Content of an attribute that may be one of crustrace's: instrument(...), crustrace::skip
+    pub struct CrustraceAttr {
+        /// Optional crate path: crustrace:: or ::crustrace::
+        pub _crate: Option<Cons<Option<PathSep>, KCrustrace, PathSep>>,
+        /// Attribute name
+        pub name: Ident,
+        /// Optional arguments in parentheses
+        pub args: Option<ParenthesisGroup>,
+    }
+
+    /// Content of an attribute as a path with optional arguments, like `tokio::test(...)`
+    pub struct AttributeMeta {
+        /// Optional leading ::
+        pub _leading: Option<PathSep>,
+        /// Path segments
+        pub segments: PathSepDelimitedVec<Ident>,
+        /// Optional arguments in parentheses
+        pub args: Option<ParenthesisGroup>,
+    }
+
+    /// `Box::pin(async move { ... })`, the body `#[async_trait]` gives an `async fn`
+    pub struct BoxPinCall {
+        /// Optional leading ::
+        pub _leading: Option<PathSep>,
+        /// Path to the function called, e.g. `Box::pin`
+        pub path: PathSepDelimitedVec<Ident>,
+        /// The async block passed to it
+        pub future: ParenthesisGroupContaining<AsyncBlock>,
+    }
+
+    /// async move { ... }
+    pub struct AsyncBlock {
+        pub _async: KAsync,
+        pub _move: Option<KMove>,
+        pub body: BraceGroup,
+    }
+
     /// Extern specification with optional ABI
     pub enum ExternSpec {
         /// "extern" with ABI string like extern "C"
@@ -318,20 +599,28 @@ unsynn! {
         Pattern(PatternParam),
     }
 
-    /// self, &self, &mut self, mut self
+    /// self, &self, &mut self, mut self, with an optional lifetime on references
     pub enum SelfParam {
         /// self
         Value(KSelf),
-        /// &self
-        Ref(Cons<And, KSelf>),
-        /// &mut self
-        RefMut(Cons<And, Cons<KMut, KSelf>>),
+        /// &self or &'a self
+        Ref(Cons<And, Option<Lifetime>, KSelf>),
+        /// &mut self or &'a mut self
+        RefMut(Cons<And, Option<Lifetime>, Cons<KMut, KSelf>>),
         /// mut self
         Mut(Cons<KMut, KSelf>),
     }
 
+    /// A lifetime: 'a
+    pub struct Lifetime {
+        pub _tick: LifetimeTick,
+        pub name: Ident,
+    }
+
     /// name: Type parameter
     pub struct NamedParam {
+        /// Optional attributes, such as #[record(Debug)]
+        pub attributes: Option<Many<Attribute>>,
         /// Optional mut keyword
         pub mut_kw: Option<KMut>,
         /// Parameter name
@@ -342,8 +631,10 @@ unsynn! {
         pub param_type: VerbatimUntil<Comma>,
     }
 
-    /// Pattern parameter like (a, b): (i32, i32) or mut (x, y): Point
+    /// Pattern parameter like (a, b): (i32, i32) or Point(x, y): Point
     pub struct PatternParam {
+        /// Optional attributes, such as #[record(Debug)]
+        pub attributes: Option<Many<Attribute>>,
         /// Optional mut keyword
         pub mut_kw: Option<KMut>,
         /// Pattern (everything before colon, could be tuple, struct pattern, etc.)
@@ -351,11 +642,13 @@ unsynn! {
         /// Colon
         pub _colon: Colon,
         /// Parameter type
-        pub param_type: VerbatimUntil<Either<Comma, ParenthesisGroup>>,
+        pub param_type: VerbatimUntil<Comma>,
     }
 
    /// Different types of patterns
     pub enum Pattern {
+        /// Tuple struct pattern: Point(x, y)
+        TupleStruct(TupleStructPattern),
         /// Simple identifier: value
         Ident(Ident),
         /// Tuple pattern: (a, b, c)
@@ -370,6 +663,14 @@ unsynn! {
         pub fields: ParenthesisGroupContaining<Option<CommaDelimitedVec<PatternField>>>,
     }
 
+    /// Tuple struct destructuring pattern: Point(x, y)
+    pub struct TupleStructPattern {
+        /// Path to the tuple struct
+        pub path: PathSepDelimitedVec<Ident>,
+        /// Its fields, as in a tuple pattern
+        pub fields: TuplePattern,
+    }
+
     /// Field in a pattern
     pub enum PatternField {
         /// Simple identifier
@@ -378,6 +679,22 @@ unsynn! {
         Nested(Pattern),
     }
 
+    /// Parameter attribute content choosing how the parameter is recorded: record(Display)
+    pub struct RecordAttr {
+        pub _record: KRecord,
+        pub kind: ParenthesisGroupContaining<RecordKind>,
+    }
+
+    /// Recording strategy named in a record attribute
+    pub enum RecordKind {
+        /// As a `tracing::Value`
+        Value(KValue),
+        /// Debug format (?)
+        Debug(KDebug),
+        /// Display format (%)
+        Display(KDisplay),
+    }
+
     /// Arguments to ret() - parsed declaratively
     pub struct RetArgs {
         /// The ret keyword, which may be bare or followed by brackets (which may contain args)
@@ -386,7 +703,15 @@ unsynn! {
         pub args: Option<ParenthesisGroupContaining<Option<CommaDelimitedVec<RetArg>>>>,
     }
 
-    /// Single argument inside ret(...)
+    /// Arguments to err() - parsed declaratively
+    pub struct ErrArgs {
+        /// The err keyword, which may be bare or followed by brackets (which may contain args)
+        pub _err: KErr,
+        /// Optional parentheses containing err arguments (same grammar as ret arguments)
+        pub args: Option<ParenthesisGroupContaining<Option<CommaDelimitedVec<RetArg>>>>,
+    }
+
+    /// Single argument inside ret(...) or err(...)
     pub enum RetArg {
         /// level = "debug"
         Level(LevelArg),
@@ -394,6 +719,8 @@ unsynn! {
         Debug(KDebug), // matches "Debug" identifier
         /// Display format mode
         Display(KDisplay), // matches "Display" identifier
+        /// Record on the span rather than as an event
+        Record(KRecord),
     }
 
     /// Format mode for return value logging
@@ -407,9 +734,275 @@ unsynn! {
 
 }
 
+/// A `tracing::Level`, as named by a `level = ...` argument
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    /// `TRACE` (1)
+    Trace,
+    /// `DEBUG` (2)
+    Debug,
+    /// `INFO` (3)
+    Info,
+    /// `WARN` (4)
+    Warn,
+    /// `ERROR` (5)
+    Error,
+}
+
+impl Level {
+    /// Look up a level by name, ignoring case
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "trace" => Some(Level::Trace),
+            "debug" => Some(Level::Debug),
+            "info" => Some(Level::Info),
+            "warn" => Some(Level::Warn),
+            "error" => Some(Level::Error),
+            _ => None,
+        }
+    }
+
+    /// Look up a level by number, from 1 (trace) to 5 (error)
+    fn from_int(value: u128) -> Option<Self> {
+        match value {
+            1 => Some(Level::Trace),
+            2 => Some(Level::Debug),
+            3 => Some(Level::Info),
+            4 => Some(Level::Warn),
+            5 => Some(Level::Error),
+            _ => None,
+        }
+    }
+}
+
+/// How a parameter is recorded as a span field
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordType {
+    /// `x = x`, for types implementing `tracing::Value`
+    Value,
+    /// `x = ?x`
+    Debug,
+    /// `x = %x`
+    Display,
+    /// As a value, with Debug or as its type name, whichever the type supports
+    Auto,
+}
+
+impl RecordType {
+    /// Types recorded as values, matched on the last path segment like `tracing-attributes`
+    const VALUE_TYPES: &[&str] = &[
+        "bool", "str", "String", "u8", "i8", "u16", "i16", "u32", "i32", "u64", "i64", "u128",
+        "i128", "usize", "isize", "f32", "f64",
+    ];
+
+    /// Choose how to record a parameter of the given type: primitive numbers, `bool`, `&str`
+    /// and `String` (behind any number of references) as values, everything else by whatever
+    /// it supports
+    pub fn from_type(ty: &proc_macro2::TokenStream) -> Self {
+        use proc_macro2::TokenTree;
+
+        let mut tokens = ty.clone().into_iter().peekable();
+        // Strip references along with their lifetimes and `mut`
+        let mut path = Vec::new();
+        while let Some(token) = tokens.next() {
+            match &token {
+                TokenTree::Punct(punct) if punct.as_char() == '&' && path.is_empty() => {}
+                TokenTree::Punct(punct) if punct.as_char() == '\'' && path.is_empty() => {
+                    tokens.next();
+                }
+                TokenTree::Ident(ident) if ident == "mut" && path.is_empty() => {}
+                _ => path.push(token),
+            }
+        }
+
+        // What remains must be a plain path, `::`-separated with no generics
+        let is_path = path.iter().all(|token| match token {
+            TokenTree::Ident(_) => true,
+            TokenTree::Punct(punct) => punct.as_char() == ':',
+            _ => false,
+        });
+        match path.last() {
+            Some(TokenTree::Ident(ident))
+                if is_path && Self::VALUE_TYPES.iter().any(|name| ident == name) =>
+            {
+                RecordType::Value
+            }
+            _ => RecordType::Auto,
+        }
+    }
+}
+
+impl RecordAttr {
+    /// The recording strategy this attribute asks for
+    pub fn record_type(&self) -> RecordType {
+        match &self.kind.content {
+            RecordKind::Value(_) => RecordType::Value,
+            RecordKind::Debug(_) => RecordType::Debug,
+            RecordKind::Display(_) => RecordType::Display,
+        }
+    }
+}
+
+impl Attribute {
+    /// Whether this is a `#[record(...)]` parameter attribute
+    pub fn is_record(&self) -> bool {
+        matches!(
+            self.content.0.stream().into_iter().next(),
+            Some(proc_macro2::TokenTree::Ident(ident)) if ident == "record"
+        )
+    }
+
+    /// The crustrace attribute `name` (possibly as `crustrace::name`) if this is one
+    fn crustrace_attr(&self, name: &str) -> Option<CrustraceAttr> {
+        let content = self.content.0.stream();
+        content
+            .to_token_iter()
+            .parse_all::<CrustraceAttr>()
+            .ok()
+            .filter(|attr| attr.name == name)
+    }
+
+    /// The arguments of an `#[instrument(...)]` attribute, or `None` if this isn't one
+    pub fn instrument_args(&self) -> Option<proc_macro2::TokenStream> {
+        let attr = self.crustrace_attr("instrument")?;
+        Some(attr.args.map(|args| args.0.stream()).unwrap_or_default())
+    }
+
+    /// Whether this is a `#[crustrace::skip]` attribute, excluding its item from `#[omni]`
+    pub fn is_skip(&self) -> bool {
+        self.crustrace_attr("skip")
+            .is_some_and(|attr| attr.args.is_none())
+    }
+
+    /// Whether this is `#[tracing::instrument]`, which instruments its function by itself
+    pub fn is_tracing_instrument(&self) -> bool {
+        let content = self.content.0.stream();
+        content
+            .to_token_iter()
+            .parse_all::<AttributeMeta>()
+            .is_ok_and(|meta| {
+                let segments: Vec<String> = meta
+                    .segments
+                    .0
+                    .iter()
+                    .map(|segment| segment.value.to_string())
+                    .collect();
+                segments == ["tracing", "instrument"]
+            })
+    }
+
+    /// Whether this marks test code: `#[cfg(test)]` (also within `all(...)` or `any(...)`),
+    /// `#[test]`, `#[bench]`, or a test attribute from another crate like `#[tokio::test]`
+    pub fn is_test(&self) -> bool {
+        let content = self.content.0.stream();
+        let Ok(meta) = content.to_token_iter().parse_all::<AttributeMeta>() else {
+            return false;
+        };
+        let segments: Vec<String> = meta
+            .segments
+            .0
+            .iter()
+            .map(|segment| segment.value.to_string())
+            .collect();
+        match (segments.as_slice(), &meta.args) {
+            ([cfg], Some(args)) if cfg == "cfg" => cfg_has_test(args.0.stream()),
+            ([.., name], _) => name == "test" || name == "bench",
+            _ => false,
+        }
+    }
+
+    /// Parse the content of a `#[record(...)]` parameter attribute
+    pub fn parse_record(&self) -> Result<RecordAttr> {
+        let content = self.content.0.stream();
+        content.to_token_iter().parse_all::<RecordAttr>()
+    }
+}
+
 // Parsing logic using unsynn declarative parsing:
+impl LevelValue {
+    /// The level named by this value, or `None` if it isn't a tracing level
+    pub fn level(&self) -> Option<Level> {
+        match self {
+            LevelValue::Str(string) => Level::from_name(string.as_str()),
+            LevelValue::Int(int) => Level::from_int(int.value()),
+            LevelValue::Path(path) => {
+                // A qualified path must end in `Level::<name>`
+                let segments: Vec<String> = path
+                    .segments
+                    .0
+                    .iter()
+                    .map(|segment| segment.value.to_string())
+                    .collect();
+                match segments.as_slice() {
+                    [name] => Level::from_name(name),
+                    [.., level, name] if level == "Level" => Level::from_name(name),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Span of the value, for pointing errors at it
+    pub fn span(&self) -> proc_macro2::Span {
+        let mut tokens = proc_macro2::TokenStream::new();
+        unsynn::ToTokens::to_tokens(self, &mut tokens);
+        tokens
+            .into_iter()
+            .last()
+            .map_or_else(proc_macro2::Span::call_site, |token| token.span())
+    }
+}
+
 impl RetArgs {
     /// Extract the effective format mode from parsed args
+    pub fn format_mode(&self) -> FormatMode {
+        self.given_format_mode().unwrap_or_default()
+    }
+
+    /// The format mode given as `ret(Debug)` or `ret(Display)`, if either was
+    pub fn given_format_mode(&self) -> Option<FormatMode> {
+        if let Some(args_group) = &self.args {
+            if let Some(arg_list) = &args_group.content {
+                for arg in &arg_list.0 {
+                    match &arg.value {
+                        RetArg::Debug(_) => return Some(FormatMode::Debug),
+                        RetArg::Display(_) => return Some(FormatMode::Display),
+                        RetArg::Level(_) | RetArg::Record(_) => continue,
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Whether `record` was given, to record the return value on the span rather than as an event
+    pub fn records(&self) -> bool {
+        self.args
+            .iter()
+            .filter_map(|args_group| args_group.content.as_ref())
+            .flat_map(|arg_list| &arg_list.0)
+            .any(|arg| matches!(arg.value, RetArg::Record(_)))
+    }
+
+    /// Extract the custom level if specified
+    pub fn custom_level(&self) -> Option<&LevelArg> {
+        if let Some(args_group) = &self.args {
+            if let Some(arg_list) = &args_group.content {
+                for arg in &arg_list.0 {
+                    if let RetArg::Level(level_arg) = &arg.value {
+                        return Some(level_arg);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl ErrArgs {
+    /// Extract the effective format mode from parsed args.
+    ///
+    /// Unlike `ret`, errors are recorded with Display unless `err(Debug)` is given.
     pub fn format_mode(&self) -> FormatMode {
         if let Some(args_group) = &self.args {
             if let Some(arg_list) = &args_group.content {
@@ -417,12 +1010,21 @@ impl RetArgs {
                     match &arg.value {
                         RetArg::Debug(_) => return FormatMode::Debug,
                         RetArg::Display(_) => return FormatMode::Display,
-                        RetArg::Level(_) => continue,
+                        RetArg::Level(_) | RetArg::Record(_) => continue,
                     }
                 }
             }
         }
-        FormatMode::default()
+        FormatMode::Display
+    }
+
+    /// Whether `record` was given, which only applies to `ret`
+    pub fn records(&self) -> bool {
+        self.args
+            .iter()
+            .filter_map(|args_group| args_group.content.as_ref())
+            .flat_map(|arg_list| &arg_list.0)
+            .any(|arg| matches!(arg.value, RetArg::Record(_)))
     }
 
     /// Extract the custom level if specified
@@ -440,32 +1042,306 @@ impl RetArgs {
     }
 }
 
+impl FieldArg {
+    /// The field name when it is a single identifier (dotted names never shadow parameters)
+    pub fn single_name(&self) -> Option<&Ident> {
+        match self.name.0.as_slice() {
+            [only] => Some(&only.value),
+            _ => None,
+        }
+    }
+
+    /// The `span!` field tokens, with `tracing` as the path to the `tracing` crate
+    pub fn to_field_tokens(&self, tracing: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let mut name = proc_macro2::TokenStream::new();
+        unsynn::ToTokens::to_tokens(&self.name, &mut name);
+
+        match (&self.kind, &self.value) {
+            // A sigil on the value takes precedence over one on the name
+            (kind, Some(value)) => {
+                let kind = value.kind.as_ref().or(kind.as_ref());
+                let mut expr = proc_macro2::TokenStream::new();
+                unsynn::ToTokens::to_tokens(&value.expr, &mut expr);
+                quote::quote!(#name = #kind #expr)
+            }
+            // `?name` / `%name` record the local variable of the same name
+            (Some(kind), None) => quote::quote!(#kind #name),
+            // A bare name declares a field to be recorded later
+            (None, None) => quote::quote!(#name = #tracing::field::Empty),
+        }
+    }
+}
+
+impl BoxPinCall {
+    /// Whether the function called is `Box::pin`, by any path
+    pub fn is_box_pin(&self) -> bool {
+        let segments: Vec<String> = self
+            .path
+            .0
+            .iter()
+            .map(|segment| segment.value.to_string())
+            .collect();
+        matches!(segments.as_slice(), [.., boxed, pin] if boxed == "Box" && pin == "pin")
+    }
+}
+
+impl ModuleItem {
+    /// The attributes on this item (none for items other than functions, impls, mods and traits)
+    pub fn attributes(&self) -> impl Iterator<Item = &Attribute> {
+        let attributes = match self {
+            ModuleItem::Function(func) => &func.attributes,
+            ModuleItem::ImplBlock(impl_block) => &impl_block.attributes,
+            ModuleItem::Module(module) => &module.attributes,
+            ModuleItem::Trait(trait_def) => &trait_def.attributes,
+            ModuleItem::Other(_) => &None,
+        };
+        attributes
+            .iter()
+            .flat_map(|attrs| &attrs.0)
+            .map(|attr| &attr.value)
+    }
+}
+
+impl ImplBlockSig {
+    /// Name of the type the impl block is for, without its path or generic arguments
+    pub fn self_type_name(&self) -> Option<String> {
+        let mut self_type = proc_macro2::TokenStream::new();
+        match &self.for_trait {
+            Some(for_trait) => unsynn::ToTokens::to_tokens(&for_trait.second, &mut self_type),
+            None => unsynn::ToTokens::to_tokens(&self.target_type, &mut self_type),
+        }
+        last_ident_name(self_type)
+    }
+
+    /// Name of the trait implemented, without its path or generic arguments, for a trait impl
+    pub fn trait_name(&self) -> Option<String> {
+        self.for_trait.as_ref()?;
+        let mut trait_path = proc_macro2::TokenStream::new();
+        unsynn::ToTokens::to_tokens(&self.target_type, &mut trait_path);
+        last_ident_name(trait_path)
+    }
+}
+
+/// The last identifier in a type or trait path outside of any `<...>`
+fn last_ident_name(path: proc_macro2::TokenStream) -> Option<String> {
+    let mut depth = 0usize;
+    let mut name = None;
+    for token in path {
+        match token {
+            proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '<' => depth += 1,
+            proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '>' => {
+                depth = depth.saturating_sub(1)
+            }
+            proc_macro2::TokenTree::Ident(ident) if depth == 0 => name = Some(ident.to_string()),
+            _ => {}
+        }
+    }
+    name
+}
+
+/// The kind of future a function returns, for `#[instrument(future)]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReturnedFuture {
+    /// `impl Future<Output = T> + ...`
+    Impl,
+    /// `Pin<Box<dyn Future<Output = T> + ...>>`
+    Boxed,
+}
+
+impl ReturnType {
+    /// The kind of future this return type is, if it is `impl Future` or a pinned boxed
+    /// `dyn Future` (by any path to `Future`, `Pin` and `Box`)
+    pub fn future_kind(&self) -> Option<ReturnedFuture> {
+        let mut return_type = proc_macro2::TokenStream::new();
+        unsynn::ToTokens::to_tokens(&self.return_type, &mut return_type);
+        let tokens: Vec<_> = return_type.into_iter().collect();
+        if is_keyword(tokens.first(), "impl") && has_future_bound(&tokens[1..]) {
+            return Some(ReturnedFuture::Impl);
+        }
+        let (pin, boxed) = generic_argument(&tokens)?;
+        let (boxed, dyn_future) = generic_argument(&boxed)?;
+        (pin == "Pin"
+            && boxed == "Box"
+            && is_keyword(dyn_future.first(), "dyn")
+            && has_future_bound(&dyn_future[1..]))
+        .then_some(ReturnedFuture::Boxed)
+    }
+
+    /// Whether this return type is a `Result`, by the last segment of its path (e.g.
+    /// `io::Result<()>` or `Result<T, E>`)
+    pub fn is_result(&self) -> bool {
+        let mut return_type = proc_macro2::TokenStream::new();
+        unsynn::ToTokens::to_tokens(&self.return_type, &mut return_type);
+        let tokens: Vec<_> = return_type.into_iter().collect();
+        let path_end = tokens
+            .iter()
+            .position(|token| matches!(token, proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '<'))
+            .unwrap_or(tokens.len());
+        let path = &tokens[..path_end];
+        path.iter().all(|token| match token {
+            proc_macro2::TokenTree::Ident(_) => true,
+            proc_macro2::TokenTree::Punct(punct) => punct.as_char() == ':',
+            _ => false,
+        }) && is_keyword(path.last(), "Result")
+    }
+}
+
+/// Whether any of the comma-separated `cfg` predicates is `test`, or an `all(...)` or `any(...)`
+/// of predicates with a `test` among them. Nothing under `not(...)` counts
+fn cfg_has_test(predicates: proc_macro2::TokenStream) -> bool {
+    let tokens: Vec<proc_macro2::TokenTree> = predicates.into_iter().collect();
+    tokens
+        .split(
+            |token| matches!(token, proc_macro2::TokenTree::Punct(punct) if punct.as_char() == ','),
+        )
+        .any(|predicate| match predicate {
+            [proc_macro2::TokenTree::Ident(ident)] => ident == "test",
+            [proc_macro2::TokenTree::Ident(ident), proc_macro2::TokenTree::Group(group)]
+                if ident == "all" || ident == "any" =>
+            {
+                cfg_has_test(group.stream())
+            }
+            _ => false,
+        })
+}
+
+fn is_keyword(token: Option<&proc_macro2::TokenTree>, keyword: &str) -> bool {
+    matches!(token, Some(proc_macro2::TokenTree::Ident(ident)) if ident == keyword)
+}
+
+/// The change in `<...>` depth at `token`, ignoring the `>` of a `->`
+fn angle_depth_change(
+    token: &proc_macro2::TokenTree,
+    previous: Option<&proc_macro2::TokenTree>,
+) -> isize {
+    match token {
+        proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '<' => 1,
+        proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '>' => match previous {
+            Some(proc_macro2::TokenTree::Punct(arrow))
+                if arrow.as_char() == '-' && arrow.spacing() == proc_macro2::Spacing::Joint =>
+            {
+                0
+            }
+            _ => -1,
+        },
+        _ => 0,
+    }
+}
+
+/// Whether any of the `+`-separated bounds is `Future` (e.g. `Future<Output = T> + Send + '_`)
+fn has_future_bound(bounds: &[proc_macro2::TokenTree]) -> bool {
+    let mut depth = 0isize;
+    let mut start = 0;
+    let is_future = |bound: &[proc_macro2::TokenTree]| {
+        last_ident_name(bound.iter().cloned().collect()).as_deref() == Some("Future")
+    };
+    for (index, token) in bounds.iter().enumerate() {
+        depth += angle_depth_change(token, index.checked_sub(1).map(|i| &bounds[i]));
+        if depth == 0
+            && matches!(token, proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '+')
+        {
+            if is_future(&bounds[start..index]) {
+                return true;
+            }
+            start = index + 1;
+        }
+    }
+    is_future(&bounds[start..])
+}
+
+/// The last segment of a path with a single generic argument, e.g. `("Pin", Box<...>)` for
+/// `std::pin::Pin<Box<...>>`
+fn generic_argument(
+    path: &[proc_macro2::TokenTree],
+) -> Option<(String, Vec<proc_macro2::TokenTree>)> {
+    let open = path.iter().position(
+        |token| matches!(token, proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '<'),
+    )?;
+    let Some(proc_macro2::TokenTree::Ident(name)) = open.checked_sub(1).map(|i| &path[i]) else {
+        return None;
+    };
+    let mut depth = 0isize;
+    for (index, token) in path.iter().enumerate().skip(open) {
+        depth += angle_depth_change(token, index.checked_sub(1).map(|i| &path[i]));
+        if depth == 0 {
+            // The path must end with its generic argument
+            return (index == path.len() - 1)
+                .then(|| (name.to_string(), path[open + 1..index].to_vec()));
+        }
+    }
+    None
+}
+
+impl FnSig {
+    /// Whether the function has its own `#[instrument(...)]` attribute
+    pub fn has_instrument_attr(&self) -> bool {
+        self.attributes
+            .iter()
+            .flat_map(|attrs| &attrs.0)
+            .any(|attr| attr.value.instrument_args().is_some())
+    }
+
+    /// Remove `#[record(...)]` attributes from the parameters, for a function that won't be
+    /// instrumented (only `#[instrument]` understands them)
+    pub fn strip_record_attrs(&mut self) {
+        for param in self
+            .params
+            .content
+            .iter_mut()
+            .flat_map(|params| &mut params.0)
+        {
+            let attributes = match &mut param.value {
+                FnParam::Named(named_param) => &mut named_param.attributes,
+                FnParam::Pattern(pattern_param) => &mut pattern_param.attributes,
+                FnParam::SelfParam(_) => continue,
+            };
+            if let Some(attrs) = attributes {
+                attrs.0.retain(|attr| !attr.value.is_record());
+            }
+        }
+    }
+
+    /// Remove the function's `#[instrument(...)]` attribute, returning its arguments
+    pub fn take_instrument_args(&mut self) -> Option<proc_macro2::TokenStream> {
+        let attrs = &mut self.attributes.as_mut()?.0;
+        let index = attrs
+            .iter()
+            .position(|attr| attr.value.instrument_args().is_some())?;
+        attrs.remove(index).value.instrument_args()
+    }
+}
+
 impl Pattern {
     pub(crate) fn extract_identifiers(&self) -> Vec<&Ident> {
         match self {
-            Pattern::Tuple(tuple) => {
-                if let Some(fields) = &tuple.fields.content {
-                    fields
-                        .0
-                        .iter()
-                        .filter_map(|field| {
-                            if let PatternField::Ident(ident) = &field.value {
-                                Some(ident)
-                            } else {
-                                None
-                            }
-                        })
-                        .collect()
-                } else {
-                    Vec::new()
-                }
-            }
+            Pattern::Tuple(tuple) => tuple.extract_identifiers(),
+            Pattern::TupleStruct(tuple_struct) => tuple_struct.fields.extract_identifiers(),
             Pattern::Ident(ident) => vec![ident],
             _ => Vec::new(),
         }
     }
 }
 
+impl TuplePattern {
+    fn extract_identifiers(&self) -> Vec<&Ident> {
+        if let Some(fields) = &self.fields.content {
+            fields
+                .0
+                .iter()
+                .filter_map(|field| {
+                    if let PatternField::Ident(ident) = &field.value {
+                        Some(ident)
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
 // Implement ToTokens for quote! compatibility
 impl quote::ToTokens for FnSig {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
@@ -523,6 +1399,28 @@ impl quote::ToTokens for FnSig {
     }
 }
 
+/// Renders the `tracing::Level` constant name, to be prefixed with the path to `Level`
+impl quote::ToTokens for Level {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        tokens.extend(match self {
+            Level::Trace => quote::quote!(TRACE),
+            Level::Debug => quote::quote!(DEBUG),
+            Level::Info => quote::quote!(INFO),
+            Level::Warn => quote::quote!(WARN),
+            Level::Error => quote::quote!(ERROR),
+        });
+    }
+}
+
+impl quote::ToTokens for FieldKind {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        match self {
+            FieldKind::Debug(question) => unsynn::ToTokens::to_tokens(question, tokens),
+            FieldKind::Display(percent) => unsynn::ToTokens::to_tokens(percent, tokens),
+        }
+    }
+}
+
 impl quote::ToTokens for FnParam {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         match self {
@@ -546,6 +1444,9 @@ impl quote::ToTokens for SelfParam {
 
 impl quote::ToTokens for NamedParam {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        if let Some(attrs) = &self.attributes {
+            unsynn::ToTokens::to_tokens(attrs, tokens);
+        }
         if let Some(mut_kw) = &self.mut_kw {
             unsynn::ToTokens::to_tokens(mut_kw, tokens);
         }
@@ -557,6 +1458,9 @@ impl quote::ToTokens for NamedParam {
 
 impl quote::ToTokens for PatternParam {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        if let Some(attrs) = &self.attributes {
+            unsynn::ToTokens::to_tokens(attrs, tokens);
+        }
         if let Some(mut_kw) = &self.mut_kw {
             unsynn::ToTokens::to_tokens(mut_kw, tokens);
         }
@@ -570,6 +1474,7 @@ impl quote::ToTokens for Pattern {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         match self {
             Pattern::Tuple(tuple) => quote::ToTokens::to_tokens(tuple, tokens),
+            Pattern::TupleStruct(tuple_struct) => quote::ToTokens::to_tokens(tuple_struct, tokens),
             Pattern::Ident(ident) => quote::ToTokens::to_tokens(ident, tokens),
             Pattern::Other(other) => unsynn::ToTokens::to_tokens(other, tokens),
         }
@@ -582,6 +1487,13 @@ impl quote::ToTokens for TuplePattern {
     }
 }
 
+impl quote::ToTokens for TupleStructPattern {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        unsynn::ToTokens::to_tokens(&self.path, tokens);
+        quote::ToTokens::to_tokens(&self.fields, tokens);
+    }
+}
+
 impl quote::ToTokens for PatternField {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         match self {
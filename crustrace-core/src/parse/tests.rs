@@ -260,6 +260,7 @@ fn test_mixed_args_with_ret() {
                     InstrumentArg::Ret(_) => found_ret = true,
                     InstrumentArg::Target(_) => found_target = true,
                     InstrumentArg::Parent(_) => found_parent = true,
+                    _ => panic!("Unexpected argument in mixed args"),
                 }
             }
 
@@ -366,3 +367,284 @@ fn test_ret_with_level_and_format() {
         Err(e) => panic!("Parse failed: {}", e),
     }
 }
+
+#[test]
+fn test_bare_err_parsing() {
+    let input = quote!(err);
+    let mut iter = input.into_token_iter();
+
+    match iter.parse::<InstrumentInner>() {
+        Ok(parsed) => {
+            let args = parsed.args.as_ref().expect("Should have parsed arguments");
+            match &args.0[0].value {
+                InstrumentArg::Err(err_args) => {
+                    assert_eq!(err_args.format_mode(), FormatMode::Display);
+                    assert!(err_args.custom_level().is_none());
+                }
+                _ => panic!("Expected Err argument"),
+            }
+        }
+        Err(e) => panic!("Parse failed: {}", e),
+    }
+}
+
+#[test]
+fn test_err_with_level_and_format() {
+    let input = quote!(err(level = "warn", Debug));
+    let mut iter = input.into_token_iter();
+
+    match iter.parse::<InstrumentInner>() {
+        Ok(parsed) => {
+            let args = parsed.args.as_ref().expect("Should have parsed arguments");
+            match &args.0[0].value {
+                InstrumentArg::Err(err_args) => {
+                    assert_eq!(err_args.format_mode(), FormatMode::Debug);
+                    let level = err_args.custom_level().expect("Should have a level");
+                    assert_eq!(level.value.level(), Some(Level::Warn));
+                }
+                _ => panic!("Expected Err argument"),
+            }
+        }
+        Err(e) => panic!("Parse failed: {}", e),
+    }
+}
+
+#[test]
+fn test_err_and_ret_parsing() {
+    let input = quote!(ret(Display), err);
+    let mut iter = input.into_token_iter();
+
+    match iter.parse::<InstrumentInner>() {
+        Ok(parsed) => {
+            let args = parsed.args.as_ref().expect("Should have parsed arguments");
+            assert_eq!(args.0.len(), 2, "Should have 2 arguments");
+            assert!(matches!(args.0[0].value, InstrumentArg::Ret(_)));
+            assert!(matches!(args.0[1].value, InstrumentArg::Err(_)));
+        }
+        Err(e) => panic!("Parse failed: {}", e),
+    }
+}
+
+#[test]
+fn test_fields_parsing() {
+    let input = quote!(fields(user_id = req.user.id, %path, ?state, otel.kind = "server", pending));
+    let mut iter = input.into_token_iter();
+
+    match iter.parse::<InstrumentInner>() {
+        Ok(parsed) => {
+            let args = parsed.args.as_ref().expect("Should have parsed arguments");
+            let InstrumentArg::Fields(fields_arg) = &args.0[0].value else {
+                panic!("Expected Fields argument");
+            };
+            let fields = &fields_arg
+                .fields
+                .content
+                .as_ref()
+                .expect("Should have fields")
+                .0;
+            assert_eq!(fields.len(), 5, "Should have 5 fields");
+
+            assert_eq!(
+                fields[0].value.single_name().unwrap().to_string(),
+                "user_id"
+            );
+            assert!(fields[0].value.value.is_some());
+            assert!(matches!(fields[1].value.kind, Some(FieldKind::Display(_))));
+            assert!(matches!(fields[2].value.kind, Some(FieldKind::Debug(_))));
+            assert!(
+                fields[3].value.single_name().is_none(),
+                "Dotted names are not single identifiers"
+            );
+            assert!(fields[4].value.kind.is_none() && fields[4].value.value.is_none());
+        }
+        Err(e) => panic!("Parse failed: {}", e),
+    }
+}
+
+#[test]
+fn test_field_value_sigils() {
+    let input = quote!(fields(a = ?x.y, b = %z, c = tracing::field::Empty));
+    let mut iter = input.into_token_iter();
+
+    match iter.parse::<InstrumentInner>() {
+        Ok(parsed) => {
+            let args = parsed.args.as_ref().expect("Should have parsed arguments");
+            let InstrumentArg::Fields(fields_arg) = &args.0[0].value else {
+                panic!("Expected Fields argument");
+            };
+            let fields = &fields_arg
+                .fields
+                .content
+                .as_ref()
+                .expect("Should have fields")
+                .0;
+            let rendered: Vec<String> = fields
+                .iter()
+                .map(|field| field.value.to_field_tokens(&quote!(tracing)).to_string())
+                .collect();
+
+            assert_eq!(rendered[0], "a = ? x . y");
+            assert_eq!(rendered[1], "b = % z");
+            assert_eq!(rendered[2], "c = tracing :: field :: Empty");
+        }
+        Err(e) => panic!("Parse failed: {}", e),
+    }
+}
+
+#[test]
+fn test_level_grammar() {
+    let cases = [
+        (quote!(level = "debug"), Some(Level::Debug)),
+        (quote!(level = "WARN"), Some(Level::Warn)),
+        (quote!(level = 1), Some(Level::Trace)),
+        (quote!(level = 5), Some(Level::Error)),
+        (quote!(level = info), Some(Level::Info)),
+        (quote!(level = Level::WARN), Some(Level::Warn)),
+        (quote!(level = ::tracing::Level::ERROR), Some(Level::Error)),
+        (quote!(level = "warning"), None),
+        (quote!(level = "Debug "), None),
+        (quote!(level = 0), None),
+        (quote!(level = tracing::WARN), None),
+    ];
+
+    for (input, expected) in cases {
+        let rendered = input.to_string();
+        let mut iter = input.into_token_iter();
+        let parsed = iter
+            .parse::<InstrumentInner>()
+            .unwrap_or_else(|e| panic!("Parse failed for {}: {}", rendered, e));
+        let args = parsed.args.as_ref().expect("Should have parsed arguments");
+        let InstrumentArg::Level(level_arg) = &args.0[0].value else {
+            panic!("Expected Level argument for {}", rendered);
+        };
+        assert_eq!(level_arg.value.level(), expected, "for {}", rendered);
+    }
+}
+
+#[test]
+fn test_impl_block_trait_name() {
+    let parse_impl = |input: TokenStream| {
+        input
+            .into_token_iter()
+            .parse::<ImplBlockSig>()
+            .expect("Failed to parse impl block")
+    };
+
+    let trait_impl = parse_impl(quote! { impl<T> core::fmt::Debug for Wrapper<T> {} });
+    assert_eq!(trait_impl.trait_name().as_deref(), Some("Debug"));
+    assert_eq!(trait_impl.self_type_name().as_deref(), Some("Wrapper"));
+
+    let generic_trait_impl = parse_impl(quote! { impl PartialEq<Other> for Widget {} });
+    assert_eq!(
+        generic_trait_impl.trait_name().as_deref(),
+        Some("PartialEq")
+    );
+
+    let inherent_impl = parse_impl(quote! { impl Widget {} });
+    assert_eq!(inherent_impl.trait_name(), None);
+}
+
+#[test]
+fn test_attribute_is_test() {
+    let is_test = |input: TokenStream| {
+        input
+            .into_token_iter()
+            .parse::<Attribute>()
+            .expect("Failed to parse attribute")
+            .is_test()
+    };
+
+    assert!(is_test(quote! { #[cfg(test)] }));
+    assert!(is_test(quote! { #[test] }));
+    assert!(is_test(quote! { #[bench] }));
+    assert!(is_test(quote! { #[tokio::test] }));
+    assert!(is_test(quote! { #[tokio::test(flavor = "multi_thread")] }));
+    assert!(is_test(
+        quote! { #[::tokio::test(flavor = "current_thread", start_paused = true)] }
+    ));
+    assert!(is_test(quote! { #[async_std::test] }));
+    assert!(is_test(quote! { #[cfg(all(test, feature = "x"))] }));
+    assert!(is_test(quote! { #[cfg(any(test, feature = "testing"))] }));
+    assert!(is_test(quote! { #[cfg(all(unix, any(test, doc)))] }));
+    assert!(!is_test(quote! { #[cfg(all(not(test), feature = "x"))] }));
+    assert!(!is_test(quote! { #[cfg(any(unix, windows))] }));
+    assert!(!is_test(quote! { #[cfg(testing)] }));
+    assert!(!is_test(quote! { #[cfg(not(test))] }));
+    assert!(!is_test(quote! { #[cfg(feature = "test")] }));
+    assert!(!is_test(quote! { #[derive(Debug)] }));
+    assert!(!is_test(quote! { #[doc = "test"] }));
+}
+
+#[test]
+fn test_self_param_with_lifetime() {
+    let input = quote! {
+        fn get<'life0>(&'life0 self, key: u32, other: &'life0 mut Self) {}
+    };
+    let sig = parse_fn_sig(input).expect("Failed to parse function");
+    let params: Vec<_> = sig.params.content.expect("Expected parameters").0;
+    assert!(matches!(
+        params[0].value,
+        FnParam::SelfParam(SelfParam::Ref(_))
+    ));
+    assert!(matches!(params[1].value, FnParam::Named(_)));
+
+    let input = quote! { fn set<'a>(&'a mut self) {} };
+    let sig = parse_fn_sig(input.clone()).expect("Failed to parse function");
+    assert!(matches!(
+        sig.params.content.as_ref().expect("Expected parameters").0[0].value,
+        FnParam::SelfParam(SelfParam::RefMut(_))
+    ));
+    assert_eq!(fn_sig_to_tokens(sig).to_string(), input.to_string());
+}
+
+#[test]
+fn test_return_type_future_kind() {
+    let future_kind = |input: TokenStream| {
+        input
+            .into_token_iter()
+            .parse::<ReturnType>()
+            .expect("Failed to parse return type")
+            .future_kind()
+    };
+
+    assert_eq!(
+        future_kind(quote! { -> impl Future<Output = u32> + '_ }),
+        Some(ReturnedFuture::Impl)
+    );
+    assert_eq!(
+        future_kind(quote! { -> impl Send + core::future::Future<Output = u32> }),
+        Some(ReturnedFuture::Impl)
+    );
+    assert_eq!(
+        future_kind(quote! { -> Pin<Box<dyn Future<Output = Box<dyn Fn() -> u32>> + Send>> }),
+        Some(ReturnedFuture::Boxed)
+    );
+    assert_eq!(future_kind(quote! { -> impl Iterator<Item = u32> }), None);
+    assert_eq!(future_kind(quote! { -> Pin<Box<dyn Fn() -> u32>> }), None);
+    assert_eq!(
+        future_kind(quote! { -> Box<dyn Future<Output = u32>> }),
+        None
+    );
+    assert_eq!(future_kind(quote! { -> Option<u32> }), None);
+}
+
+#[test]
+fn test_return_type_is_result() {
+    let is_result = |input: TokenStream| {
+        input
+            .into_token_iter()
+            .parse::<ReturnType>()
+            .expect("Failed to parse return type")
+            .is_result()
+    };
+
+    assert!(is_result(quote! { -> Result<u32, Error> }));
+    assert!(is_result(quote! { -> std::io::Result<()> }));
+    assert!(is_result(quote! { -> ::anyhow::Result<Vec<u8>> }));
+    assert!(!is_result(quote! { -> u32 }));
+    assert!(!is_result(quote! { -> Option<Result<u32, Error>> }));
+    assert!(!is_result(quote! { -> &Result<u32, Error> }));
+    assert!(!is_result(
+        quote! { -> impl Future<Output = Result<u32, Error>> }
+    ));
+}
@@ -1,16 +1,127 @@
-use crate::instrument_impl;
+use crate::tracer::{instrument_module_function, FunctionNaming};
 use proc_macro2::TokenStream;
 use unsynn::*;
 
-use crate::parse::{ImplBlockSig, ModuleContent, ModuleItem, ModuleSig, TraitSig};
+use crate::filters::{
+    FnVisibility, ModulePaths, NameFilters, SpanNames, TraitImplFilter, VisibilityPolicy,
+};
+use crate::parse::{
+    FnSig, ImplBlockSig, ModuleContent, ModuleItem, ModuleSig, TraitSig, Visibility,
+};
 
 pub(crate) struct TokenProcessor {
     input: TokenStream,
+    /// Instrument arguments applied to every function
+    args: TokenStream,
+    /// Which functions to instrument
+    filters: NameFilters,
+    /// Levels and inclusion by visibility
+    policy: VisibilityPolicy,
+    /// Which trait impls to instrument
+    trait_impls: TraitImplFilter,
+    /// How methods' spans are named
+    span_names: SpanNames,
+    /// Whether nested module paths are added to span names and targets
+    module_paths: ModulePaths,
+    /// Whether to instrument items declared inside function bodies
+    deep: bool,
+    /// Whether to instrument test modules and functions
+    include_tests: bool,
+    /// Names of the modules nested inside the one `#[omni]` is applied to that lead to the
+    /// items being processed, or `None` for the item `#[omni]` is applied to itself
+    modules: Option<Vec<String>>,
+    /// Name of the type or trait whose impl block or trait body is being processed
+    owner: Option<String>,
+    /// Name of the trait implemented by the impl block being processed
+    impl_trait: Option<String>,
+    /// Whether the functions being processed are trait items, which have no visibility of their
+    /// own
+    trait_items: bool,
+    /// Whether the items being processed are inside one that is left uninstrumented, such as a
+    /// `#[crustrace::skip]` module, so only their `#[record]` attributes are removed
+    passing_over: bool,
 }
 
 impl TokenProcessor {
     pub(crate) fn new(input: TokenStream) -> Self {
-        Self { input }
+        Self {
+            input,
+            args: TokenStream::new(),
+            filters: NameFilters::default(),
+            policy: VisibilityPolicy::default(),
+            trait_impls: TraitImplFilter::default(),
+            span_names: SpanNames::default(),
+            module_paths: ModulePaths::default(),
+            deep: false,
+            include_tests: false,
+            modules: None,
+            owner: None,
+            impl_trait: None,
+            trait_items: false,
+            passing_over: false,
+        }
+    }
+
+    pub(crate) fn with_args(mut self, args: TokenStream) -> Self {
+        self.args = args;
+        self
+    }
+
+    pub(crate) fn with_filters(mut self, filters: NameFilters) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    pub(crate) fn with_policy(mut self, policy: VisibilityPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub(crate) fn with_trait_impls(mut self, trait_impls: TraitImplFilter) -> Self {
+        self.trait_impls = trait_impls;
+        self
+    }
+
+    pub(crate) fn with_span_names(mut self, span_names: SpanNames) -> Self {
+        self.span_names = span_names;
+        self
+    }
+
+    pub(crate) fn with_module_paths(mut self, module_paths: ModulePaths) -> Self {
+        self.module_paths = module_paths;
+        self
+    }
+
+    pub(crate) fn with_deep(mut self, deep: bool) -> Self {
+        self.deep = deep;
+        self
+    }
+
+    pub(crate) fn with_include_tests(mut self, include_tests: bool) -> Self {
+        self.include_tests = include_tests;
+        self
+    }
+
+    fn with_modules(mut self, modules: Option<Vec<String>>) -> Self {
+        self.modules = modules;
+        self
+    }
+
+    fn with_owner(
+        mut self,
+        owner: Option<String>,
+        impl_trait: Option<String>,
+        trait_items: bool,
+    ) -> Self {
+        self.owner = owner;
+        self.impl_trait = impl_trait;
+        self.trait_items = trait_items;
+        self
+    }
+
+    fn with_passing_over(mut self, passing_over: bool) -> Self {
+        self.passing_over = passing_over;
+        self
     }
 
     pub(crate) fn process(self) -> TokenStream {
@@ -40,12 +151,46 @@ impl TokenProcessor {
     }
 
     fn process_module_item(&self, item: ModuleItem) -> TokenStream {
+        // `#[crustrace::skip]` leaves an item and everything inside it uninstrumented, as do
+        // `#[tracing::instrument]` (which already instruments its function), `#[cfg(test)]`
+        // items and test functions unless `include_tests` is given, and impls of std traits like
+        // `Debug` unless opted back in
+        let passed_over = item
+            .attributes()
+            .any(|attr| attr.is_skip() || attr.is_tracing_instrument())
+            || (!self.include_tests && item.attributes().any(|attr| attr.is_test()))
+            || matches!(&item, ModuleItem::ImplBlock(impl_block) if impl_block
+                .trait_name()
+                .is_some_and(|name| !self.trait_impls.allows(&name)));
+        if passed_over && !self.passing_over {
+            return self.passing_over_processor().process_module_item(item);
+        }
+
         match item {
+            // Functions passed over are left as they are, bodies included, other than the
+            // `#[record]` attributes that `#[instrument]` would have removed. So are `const fn`s,
+            // which can't create spans, and functions the name filters or visibility policy rule
+            // out
+            ModuleItem::Function(func_sig)
+                if self.passing_over
+                    || func_sig.const_kw.is_some()
+                    || !self
+                        .filters
+                        .allows(&func_sig.name.to_string(), self.owner.as_deref())
+                    || !self.policy.allows(self.visibility(&func_sig)) =>
+            {
+                Self::without_record_attrs(func_sig).to_token_stream()
+            }
             ModuleItem::Function(func_sig) => {
-                // Convert using unsynn's ToTokens trait
+                let mut func_sig = self.process_fn_body(func_sig);
+                let policy_args = self.policy.level_args(self.visibility(&func_sig)).cloned();
+                // A function's own `#[instrument]` is merged into the module's, so that it is
+                // only expanded once
+                let fn_args = func_sig.take_instrument_args();
+                let naming = self.naming(&func_sig);
                 let mut func_tokens = TokenStream::new();
                 quote::ToTokens::to_tokens(&func_sig, &mut func_tokens);
-                self.instrument_function(func_tokens)
+                self.instrument_function(func_tokens, policy_args, fn_args, naming)
             }
             ModuleItem::ImplBlock(impl_block) => self.process_impl_block(impl_block),
             ModuleItem::Module(module) => self.process_module_block(module),
@@ -61,7 +206,16 @@ impl TokenProcessor {
 
     fn process_impl_block(&self, impl_block: ImplBlockSig) -> TokenStream {
         // Process the body content to instrument any functions inside
-        let processed_body = self.process_brace_group_content(impl_block.body.into());
+        let owner = impl_block.self_type_name();
+        let impl_trait = impl_block.trait_name();
+        let trait_impl = impl_block.for_trait.is_some();
+        let processed_body = self.process_brace_group_content(
+            impl_block.body.into(),
+            self.modules.clone(),
+            owner,
+            impl_trait,
+            trait_impl,
+        );
 
         // Reconstruct the impl block with processed body
         let mut output = TokenStream::new();
@@ -95,14 +249,20 @@ impl TokenProcessor {
         }
 
         // Add processed body
-        output.extend(processed_body);
+        processed_body.to_tokens(&mut output);
 
         output
     }
 
     fn process_module_block(&self, module: ModuleSig) -> TokenStream {
-        // Process the module body content recursively
-        let processed_body = self.process_brace_group_content(module.body.into());
+        // Process the module body content recursively, one module deeper unless this is the
+        // module `#[omni]` is applied to
+        let modules = match &self.modules {
+            Some(modules) => [modules.as_slice(), &[module.name.to_string()]].concat(),
+            None => Vec::new(),
+        };
+        let processed_body =
+            self.process_brace_group_content(module.body.into(), Some(modules), None, None, false);
 
         // Reconstruct the module with processed body
         let mut output = TokenStream::new();
@@ -124,14 +284,21 @@ impl TokenProcessor {
         module.name.to_tokens(&mut output);
 
         // Add processed body
-        output.extend(processed_body);
+        processed_body.to_tokens(&mut output);
 
         output
     }
 
     fn process_trait_block(&self, trait_def: TraitSig) -> TokenStream {
         // Process the trait body content to instrument any default implementations
-        let processed_body = self.process_brace_group_content(trait_def.body.into());
+        let owner = Some(trait_def.name.to_string());
+        let processed_body = self.process_brace_group_content(
+            trait_def.body.into(),
+            self.modules.clone(),
+            owner,
+            None,
+            true,
+        );
 
         // Reconstruct the trait with processed body
         let mut output = TokenStream::new();
@@ -173,30 +340,147 @@ impl TokenProcessor {
         }
 
         // Add processed body
-        output.extend(processed_body);
+        processed_body.to_tokens(&mut output);
 
         output
     }
 
-    fn process_brace_group_content(&self, brace_group: proc_macro2::Group) -> TokenStream {
+    fn process_brace_group_content(
+        &self,
+        brace_group: proc_macro2::Group,
+        modules: Option<Vec<String>>,
+        owner: Option<String>,
+        impl_trait: Option<String>,
+        trait_items: bool,
+    ) -> proc_macro2::Group {
         // Recursively process the content inside braces
         let inner_content = brace_group.stream();
-        let processed_inner = TokenProcessor::new(inner_content).process();
+        let processed_inner = TokenProcessor::new(inner_content)
+            .with_args(self.args.clone())
+            .with_filters(self.filters.clone())
+            .with_policy(self.policy.clone())
+            .with_trait_impls(self.trait_impls.clone())
+            .with_span_names(self.span_names)
+            .with_module_paths(self.module_paths)
+            .with_deep(self.deep)
+            .with_include_tests(self.include_tests)
+            .with_modules(modules)
+            .with_owner(owner, impl_trait, trait_items)
+            .with_passing_over(self.passing_over)
+            .process();
 
         // Wrap in braces again
-        let mut output = TokenStream::new();
-        let new_group = proc_macro2::Group::new(proc_macro2::Delimiter::Brace, processed_inner);
-        output.extend(std::iter::once(proc_macro2::TokenTree::Group(new_group)));
-        output
+        let mut new_group = proc_macro2::Group::new(proc_macro2::Delimiter::Brace, processed_inner);
+        new_group.set_span(brace_group.span());
+        new_group
     }
 
-    fn instrument_function(&self, func_tokens: TokenStream) -> TokenStream {
-        match instrument_impl(TokenStream::new(), func_tokens.clone()) {
-            Ok(instrumented) => instrumented,
-            Err(e) => {
-                eprintln!("instrument_impl failed: {}", e);
-                func_tokens // fallback to original
+    /// A processor for an item left uninstrumented along with everything inside it
+    fn passing_over_processor(&self) -> Self {
+        TokenProcessor::new(TokenStream::new())
+            .with_args(self.args.clone())
+            .with_filters(self.filters.clone())
+            .with_policy(self.policy.clone())
+            .with_trait_impls(self.trait_impls.clone())
+            .with_span_names(self.span_names)
+            .with_module_paths(self.module_paths)
+            .with_deep(self.deep)
+            .with_include_tests(self.include_tests)
+            .with_modules(self.modules.clone())
+            .with_owner(
+                self.owner.clone(),
+                self.impl_trait.clone(),
+                self.trait_items,
+            )
+            .with_passing_over(true)
+    }
+
+    /// A function left uninstrumented, without the `#[record]` attributes on its parameters,
+    /// unless its own `#[instrument]` is left to handle them
+    fn without_record_attrs(mut func_sig: FnSig) -> ModuleItem {
+        if !func_sig.has_instrument_attr() {
+            func_sig.strip_record_attrs();
+        }
+        ModuleItem::Function(func_sig)
+    }
+
+    /// In `deep` mode, instrument the items declared at the top level of a function's body
+    fn process_fn_body(&self, mut func_sig: FnSig) -> FnSig {
+        if self.deep {
+            let body = func_sig.body.0.clone();
+            let processed_body =
+                self.process_brace_group_content(body, self.modules.clone(), None, None, false);
+            func_sig.body = BraceGroup(processed_body);
+        }
+        func_sig
+    }
+
+    /// The declared visibility of a function, for the visibility policy
+    fn visibility(&self, func_sig: &FnSig) -> FnVisibility {
+        match &func_sig.visibility {
+            _ if self.trait_items => FnVisibility::Public,
+            Some(Visibility::Public(_)) => FnVisibility::Public,
+            Some(Visibility::Restricted(_)) => FnVisibility::Restricted,
+            None => FnVisibility::Private,
+        }
+    }
+
+    /// The span name, qualified path and target suffix of a function, from the impl block or
+    /// trait and the nested modules it is in
+    fn naming(&self, func_sig: &FnSig) -> FunctionNaming {
+        let name = func_sig.name.to_string();
+        let module_path = self
+            .modules
+            .as_ref()
+            .filter(|modules| !modules.is_empty())
+            .map(|modules| modules.join("::"));
+        let prefix = match &module_path {
+            Some(module_path) if self.module_paths.names => format!("{module_path}::"),
+            _ => String::new(),
+        };
+
+        let mut naming = match &self.owner {
+            Some(owner) => {
+                let mut naming = self.span_names.method_name(
+                    &name,
+                    &format!("{prefix}{owner}"),
+                    self.impl_trait.as_deref(),
+                );
+                // Short method names still get the module path
+                if self.span_names == SpanNames::Short {
+                    naming.span_name = Some(format!("{prefix}{name}"));
+                }
+                naming
             }
+            None if !prefix.is_empty() => FunctionNaming {
+                span_name: Some(format!("{prefix}{name}")),
+                ..FunctionNaming::default()
+            },
+            None => FunctionNaming::default(),
+        };
+        if self.module_paths.targets {
+            naming.target_suffix = module_path;
+        }
+        naming
+    }
+
+    fn instrument_function(
+        &self,
+        func_tokens: TokenStream,
+        policy_args: Option<TokenStream>,
+        fn_args: Option<TokenStream>,
+        naming: FunctionNaming,
+    ) -> TokenStream {
+        // An error comes with the function as written, so it is the only one reported
+        match instrument_module_function(
+            self.args.clone(),
+            policy_args,
+            fn_args,
+            naming,
+            func_tokens,
+        ) {
+            Ok(instrumented) => instrumented,
+            Err(error_with_function) => error_with_function,
         }
     }
 }
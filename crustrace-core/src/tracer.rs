@@ -3,43 +3,277 @@
 //! This crate provides the [`#[instrument]`] attribute macro using `unsynn` for parsing,
 //! offering a lightweight alternative to the standard `tracing-attributes` crate.
 
-use crate::parse::{FnParam, RetArgs};
+use crate::diagnostics::{first_span, Diagnostic};
+use crate::parse::{
+    Attribute, ErrArgs, FieldArg, FnParam, Level, LevelArg, LevelValue, RecordType, RetArgs,
+    SpanEvent, VerbatimUntil,
+};
 use core::result::Result;
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use unsynn::*;
 
-use crate::parse::{FnSig, InstrumentArg, InstrumentInner};
+use crate::parse::{BoxPinCall, FnSig, InstrumentArg, ReturnType, ReturnedFuture};
 
+/// Instrument a function, or report why it can't be instrumented alongside the function as
+/// written, so that the error is the only one reported and uses of the function still resolve
 pub fn instrument_impl(args: TokenStream, item: TokenStream) -> Result<TokenStream, TokenStream> {
-    // Parse the instrument arguments
-    let mut args_iter = args.to_token_iter();
-    let instrument_args = if args.is_empty() {
-        InstrumentArgs::default()
-    } else {
-        match parse_instrument_args(&mut args_iter) {
-            Ok(args) => args,
-            Err(e) => return Err(quote! { compile_error!(#e) }),
+    instrument(args, item.clone()).map_err(|error| with_item(error, item))
+}
+
+/// The `compile_error!` for `error`, followed by `item` as written
+fn with_item(error: Diagnostic, item: TokenStream) -> TokenStream {
+    let error = error.into_compile_error();
+    let item = without_record_attrs(item);
+    quote! { #error #item }
+}
+
+/// `item` without the `#[record]` attributes on its parameters if it is a function, as they
+/// would otherwise fail to resolve once `#[instrument]` no longer handles them
+fn without_record_attrs(item: TokenStream) -> TokenStream {
+    match item.to_token_iter().parse_all::<FnSig>() {
+        Ok(mut func_sig) => {
+            func_sig.strip_record_attrs();
+            quote! { #func_sig }
         }
-    };
+        Err(_) => item,
+    }
+}
+
+/// Instrument a function inside `#[omni]` with the module's arguments, overridden argument by
+/// argument by those of the module's visibility policy for the function, then by those of the
+/// function's own `#[instrument(...)]` attribute if it had one. Like [`instrument_impl`], an
+/// error is reported alongside the function as written
+pub(crate) fn instrument_module_function(
+    module_args: TokenStream,
+    policy_args: Option<TokenStream>,
+    fn_args: Option<TokenStream>,
+    naming: FunctionNaming,
+    item: TokenStream,
+) -> Result<TokenStream, TokenStream> {
+    instrument_in_module(module_args, policy_args, fn_args, naming, item.clone())
+        .map_err(|error| with_item(error, item))
+}
+
+/// How `#[omni]` names a function's span and reports it, beyond its arguments
+#[derive(Debug, Default)]
+pub(crate) struct FunctionNaming {
+    /// Default span name, unless a `name` argument is given
+    pub(crate) span_name: Option<String>,
+    /// Fully-qualified path of a method, recorded as the `code.function.name` field
+    pub(crate) qualified: Option<String>,
+    /// Path of the nested module the function is in, appended to the module's `target`
+    pub(crate) target_suffix: Option<String>,
+}
+
+fn instrument(args: TokenStream, item: TokenStream) -> Result<TokenStream, Diagnostic> {
+    let mut instrument_args = parse_args(&args)?;
 
     // Parse the function
     let mut item_iter = item.to_token_iter();
-    let func = match parse_simple_function(&mut item_iter) {
-        Ok(func) => func,
-        Err(e) => return Err(quote! { compile_error!(#e) }),
+    let mut func = parse_simple_function(&mut item_iter)?;
+
+    // Every skipped parameter must exist in the function signature
+    validate_skips(&instrument_args, &func.params)?;
+    validate_record_attrs(&func.params)?;
+
+    // Creating a span isn't `const`, so a `const fn` can only be instrumented as a non-const
+    // version, with an uninstrumented `const` twin for const contexts
+    match (&func.const_kw, instrument_args.const_twin.take()) {
+        (Some(const_kw), None) => Err(Diagnostic::new(
+            first_span(const_kw),
+            "`#[instrument]` can't be applied to a `const fn`, as creating a span isn't const; \
+             add `const_twin = <name>` to instrument a non-const version and keep a `const fn` \
+             copy named `<name>`",
+        )),
+        (None, Some(twin_name)) => Err(Diagnostic::new(
+            twin_name.span(),
+            "`const_twin` only applies to a `const fn`",
+        )),
+        (Some(_), Some(twin_name)) => {
+            let twin = const_twin(&func, &twin_name);
+            func.const_kw = None;
+            let instrumented = generate_instrumented_function(instrument_args, func)?;
+            Ok(quote!(#twin #instrumented))
+        }
+        (None, None) => generate_instrumented_function(instrument_args, func),
+    }
+}
+
+/// The `const fn` named `name` with the same signature and body as `func`
+fn const_twin(func: &SimpleFunction, name: &Ident) -> TokenStream {
+    let SimpleFunction {
+        attrs,
+        vis,
+        const_kw,
+        async_kw,
+        unsafe_kw,
+        extern_kw,
+        generics,
+        params,
+        ret_type,
+        where_clause,
+        body,
+        ..
+    } = func;
+    let params = strip_record_attrs(params);
+    quote! {
+        #(#attrs)*
+        #vis #const_kw #async_kw #unsafe_kw #extern_kw fn #name #generics #params #ret_type #where_clause #body
+    }
+}
+
+fn instrument_in_module(
+    module_args: TokenStream,
+    policy_args: Option<TokenStream>,
+    fn_args: Option<TokenStream>,
+    naming: FunctionNaming,
+    item: TokenStream,
+) -> Result<TokenStream, Diagnostic> {
+    let mut module_args = parse_args(&module_args)?;
+    if let Some(policy_args) = policy_args {
+        module_args = parse_args(&policy_args)?.or(module_args);
+    }
+    module_args.name = module_args.name.or(naming.span_name);
+    module_args.qualified_name = naming.qualified;
+    if let (Some(target), Some(suffix)) = (&mut module_args.target, naming.target_suffix) {
+        *target = format!("{target}::{suffix}");
+    }
+
+    let mut item_iter = item.to_token_iter();
+    let func = parse_simple_function(&mut item_iter)?;
+
+    // The module's `ret` and `err` apply whatever its functions return: `err` only to those
+    // returning a `Result`, and `ret` records values the way parameters are unless given a
+    // format, so those neither `Value` nor `Debug` are recorded by type name
+    module_args.ret_fallback = module_args
+        .ret_args
+        .as_ref()
+        .is_some_and(|ret_args| ret_args.given_format_mode().is_none());
+    if !returns_result(func.ret_type.as_ref()) {
+        module_args.err_args = None;
+    }
+
+    // The module's `skip(...)` may name parameters that only some of its functions have, but
+    // the function's own must all exist
+    let instrument_args = match fn_args {
+        Some(fn_args) => {
+            let fn_args = parse_args(&fn_args)?;
+            validate_skips(&fn_args, &func.params)?;
+            fn_args.or(module_args)
+        }
+        None => module_args,
     };
+    validate_record_attrs(&func.params)?;
+
+    generate_instrumented_function(instrument_args, func)
+}
+
+/// Parse the instrument arguments, which may be empty
+fn parse_args(args: &TokenStream) -> Result<InstrumentArgs, Diagnostic> {
+    if args.is_empty() {
+        Ok(InstrumentArgs::default())
+    } else {
+        parse_instrument_args(&mut args.to_token_iter())
+    }
+}
 
-    Ok(generate_instrumented_function(instrument_args, func))
+/// Arguments accepted by `#[instrument(...)]`, listed in error messages
+pub(crate) const INSTRUMENT_ARGS: &str =
+    "level, name, target, parent, follows_from, ret, err, skip, skip_all, fields, crate, const_twin, \
+     future, events, timing";
+
+/// The expected form of each instrument argument, for errors on malformed arguments
+pub(crate) fn argument_usage(name: &str) -> Option<&'static str> {
+    match name {
+        "level" => Some("`level = \"info\"`, `level = 1..=5` or `level = Level::INFO`"),
+        "name" => Some("`name = \"...\"`"),
+        "target" => Some("`target = \"...\"`"),
+        "parent" => Some("`parent = <expression>`"),
+        "follows_from" => Some("`follows_from = <iterator of span ids>`"),
+        "ret" => {
+            Some("`ret`, `ret(Debug | Display, level = ...)` or `ret(Debug | Display, record)`")
+        }
+        "err" => Some("`err` or `err(Debug | Display, level = ...)`"),
+        "skip" => Some("`skip(param, ...)`"),
+        "skip_all" => Some("`skip_all`"),
+        "fields" => Some("`fields(name = value, ?name, %name, ...)`"),
+        "crate" => Some("`crate = path::to::tracing`"),
+        "const_twin" => Some("`const_twin = <name>`"),
+        "future" => Some("`future`"),
+        "events" => Some("`events(enter)`, `events(exit)` or `events(enter, exit)`"),
+        "timing" => Some("`timing`"),
+        _ => None,
+    }
 }
 
 #[derive(Debug, Default)]
-struct InstrumentArgs {
-    level: Option<String>,
+pub(crate) struct InstrumentArgs {
+    level: Option<LevelArg>,
     name: Option<String>,
     ret_args: Option<RetArgs>,
+    /// Record the return value like a parameter of unknown type, for `#[omni]`'s `ret`
+    ret_fallback: bool,
+    err_args: Option<ErrArgs>,
     target: Option<String>,
     parent: Option<TokenStream>,
+    follows_from: Option<TokenStream>,
+    skips: Option<Vec<Ident>>,
+    skip_all: bool,
+    fields: Option<Vec<FieldArg>>,
+    crate_path: Option<TokenStream>,
+    const_twin: Option<Ident>,
+    /// Instrument the future a sync fn returns, with the span of the `future` argument
+    future: Option<Span>,
+    events: Option<SpanEvents>,
+    /// Record the function's duration as `elapsed_us`
+    timing: bool,
+    /// Recorded as `code.function.name`, for methods instrumented by `#[omni]`
+    qualified_name: Option<String>,
+}
+
+impl InstrumentArgs {
+    /// These arguments, with any that weren't given taken from `defaults`
+    fn or(self, defaults: InstrumentArgs) -> InstrumentArgs {
+        // `skip` and `skip_all` are alternatives, so they are overridden together
+        let (skips, skip_all) = if self.skips.is_some() || self.skip_all {
+            (self.skips, self.skip_all)
+        } else {
+            (defaults.skips, defaults.skip_all)
+        };
+        // The fallback for recording the return value goes with the `ret` it was set for
+        let ret_fallback = if self.ret_args.is_some() {
+            self.ret_fallback
+        } else {
+            defaults.ret_fallback
+        };
+        InstrumentArgs {
+            level: self.level.or(defaults.level),
+            name: self.name.or(defaults.name),
+            ret_args: self.ret_args.or(defaults.ret_args),
+            ret_fallback,
+            err_args: self.err_args.or(defaults.err_args),
+            target: self.target.or(defaults.target),
+            parent: self.parent.or(defaults.parent),
+            follows_from: self.follows_from.or(defaults.follows_from),
+            skips,
+            skip_all,
+            fields: self.fields.or(defaults.fields),
+            crate_path: self.crate_path.or(defaults.crate_path),
+            const_twin: self.const_twin.or(defaults.const_twin),
+            future: self.future.or(defaults.future),
+            events: self.events.or(defaults.events),
+            timing: self.timing || defaults.timing,
+            qualified_name: self.qualified_name.or(defaults.qualified_name),
+        }
+    }
+}
+
+/// Events emitted on entering and exiting a function, from `events(enter, exit)`
+#[derive(Debug, Default, Clone, Copy)]
+struct SpanEvents {
+    enter: bool,
+    exit: bool,
 }
 
 struct SimpleFunction {
@@ -57,45 +291,202 @@ struct SimpleFunction {
     body: TokenStream,
 }
 
-fn parse_instrument_args(input: &mut TokenIter) -> Result<InstrumentArgs, String> {
-    match input.parse::<InstrumentInner>() {
-        Ok(parsed) => {
-            let mut args = InstrumentArgs::default();
-
-            if let Some(arg_list) = parsed.args {
-                for arg in arg_list.0 {
-                    match arg.value {
-                        InstrumentArg::Level(level_arg) => {
-                            args.level = Some(level_arg.value.as_str().to_string());
-                        }
-                        InstrumentArg::Name(name_arg) => {
-                            args.name = Some(name_arg.value.as_str().to_string());
-                        }
-                        InstrumentArg::Ret(ret_args) => {
-                            if args.ret_args.is_some() {
-                                return Err("expected only a single `ret` argument".to_string());
-                            }
-                            args.ret_args = Some(ret_args);
-                        }
-                        InstrumentArg::Target(target_arg) => {
-                            args.target = Some(target_arg.value.as_str().to_string());
-                        }
-                        InstrumentArg::Parent(parent_arg) => {
-                            let mut parent_tokens = proc_macro2::TokenStream::new();
-                            unsynn::ToTokens::to_tokens(&parent_arg.value, &mut parent_tokens);
-                            args.parent = Some(parent_tokens);
-                        }
+pub(crate) fn parse_instrument_args(input: &mut TokenIter) -> Result<InstrumentArgs, Diagnostic> {
+    // Split on top-level commas first, so each argument is parsed and reported on its own
+    let arg_list = input
+        .parse_all::<CommaDelimitedVec<VerbatimUntil<Comma>>>()
+        .map_err(|e| {
+            Diagnostic::from_parse_error(
+                &e,
+                Span::call_site(),
+                "expected comma-separated instrument arguments",
+            )
+        })?;
+
+    let mut args = InstrumentArgs::default();
+    let mut skip_all_span = None;
+
+    for arg_tokens in arg_list.0 {
+        let arg_tokens = arg_tokens.value.to_token_stream();
+        let span = first_span(&arg_tokens);
+        match parse_arg::<InstrumentArg>(
+            &arg_tokens,
+            "instrument",
+            INSTRUMENT_ARGS,
+            argument_usage,
+        )? {
+            InstrumentArg::Level(level_arg) => {
+                args.level = Some(level_arg);
+            }
+            InstrumentArg::Name(name_arg) => {
+                args.name = Some(name_arg.value.as_str().to_string());
+            }
+            InstrumentArg::Ret(ret_args) => {
+                if args.ret_args.is_some() {
+                    return Err(Diagnostic::new(
+                        span,
+                        "expected only a single `ret` argument",
+                    ));
+                }
+                if ret_args.records() && ret_args.custom_level().is_some() {
+                    return Err(Diagnostic::new(
+                        span,
+                        "`ret(record)` records the return value on the span rather than as an \
+                         event, so it takes no `level`",
+                    ));
+                }
+                args.ret_args = Some(ret_args);
+            }
+            InstrumentArg::Err(err_args) => {
+                if args.err_args.is_some() {
+                    return Err(Diagnostic::new(
+                        span,
+                        "expected only a single `err` argument",
+                    ));
+                }
+                if err_args.records() {
+                    return Err(Diagnostic::new(span, "`record` only applies to `ret`"));
+                }
+                args.err_args = Some(err_args);
+            }
+            InstrumentArg::Target(target_arg) => {
+                args.target = Some(target_arg.value.as_str().to_string());
+            }
+            InstrumentArg::Skip(skip_arg) => {
+                if args.skips.is_some() {
+                    return Err(Diagnostic::new(
+                        span,
+                        "expected only a single `skip` argument",
+                    ));
+                }
+                let skips = skip_arg
+                    .params
+                    .content
+                    .map(|idents| idents.0.into_iter().map(|d| d.value).collect())
+                    .unwrap_or_default();
+                args.skips = Some(skips);
+            }
+            InstrumentArg::SkipAll(_) => {
+                args.skip_all = true;
+                skip_all_span = Some(span);
+            }
+            InstrumentArg::Fields(fields_arg) => {
+                if args.fields.is_some() {
+                    return Err(Diagnostic::new(
+                        span,
+                        "expected only a single `fields` argument",
+                    ));
+                }
+                let fields = fields_arg
+                    .fields
+                    .content
+                    .map(|fields| fields.0.into_iter().map(|d| d.value).collect())
+                    .unwrap_or_default();
+                args.fields = Some(fields);
+            }
+            InstrumentArg::Crate(crate_arg) => {
+                let mut crate_path = TokenStream::new();
+                unsynn::ToTokens::to_tokens(&crate_arg.path, &mut crate_path);
+                args.crate_path = Some(crate_path);
+            }
+            InstrumentArg::FollowsFrom(follows_from_arg) => {
+                if args.follows_from.is_some() {
+                    return Err(Diagnostic::new(
+                        span,
+                        "expected only a single `follows_from` argument",
+                    ));
+                }
+                let mut follows_from_tokens = TokenStream::new();
+                unsynn::ToTokens::to_tokens(&follows_from_arg.value, &mut follows_from_tokens);
+                args.follows_from = Some(follows_from_tokens);
+            }
+            InstrumentArg::ConstTwin(const_twin_arg) => {
+                args.const_twin = Some(const_twin_arg.name);
+            }
+            InstrumentArg::Future(_) => {
+                args.future = Some(span);
+            }
+            InstrumentArg::Events(events_arg) => {
+                if args.events.is_some() {
+                    return Err(Diagnostic::new(
+                        span,
+                        "expected only a single `events` argument",
+                    ));
+                }
+                let mut events = SpanEvents::default();
+                for kind in events_arg.kinds.content.0 {
+                    match kind.value {
+                        SpanEvent::Enter(_) => events.enter = true,
+                        SpanEvent::Exit(_) => events.exit = true,
                     }
                 }
+                if !(events.enter || events.exit) {
+                    return Err(Diagnostic::new(
+                        span,
+                        "expected `events(enter)`, `events(exit)` or `events(enter, exit)`",
+                    ));
+                }
+                args.events = Some(events);
+            }
+            InstrumentArg::Timing(_) => {
+                args.timing = true;
+            }
+            InstrumentArg::Parent(parent_arg) => {
+                let mut parent_tokens = proc_macro2::TokenStream::new();
+                unsynn::ToTokens::to_tokens(&parent_arg.value, &mut parent_tokens);
+                args.parent = Some(parent_tokens);
             }
-
-            Ok(args)
         }
-        Err(e) => Err(format!("Failed to parse instrument args: {}", e)),
     }
+
+    if let (Some(span), Some(_)) = (skip_all_span, &args.skips) {
+        return Err(Diagnostic::new(
+            span,
+            "expected either `skip` or `skip_all` argument",
+        ));
+    }
+
+    Ok(args)
 }
 
-fn parse_simple_function(input: &mut TokenIter) -> Result<SimpleFunction, String> {
+/// Parse a single `#[instrument]` or `#[omni]` argument, explaining what was expected if it
+/// isn't one of the `expected` arguments, whose forms are given by `usage`
+pub(crate) fn parse_arg<T: Parse>(
+    tokens: &TokenStream,
+    attribute: &str,
+    expected: &str,
+    usage: fn(&str) -> Option<&'static str>,
+) -> Result<T, Diagnostic> {
+    tokens
+        .to_token_iter()
+        .parse_all::<T>()
+        .map_err(|e| match tokens.clone().into_iter().next() {
+            Some(TokenTree::Ident(ident)) => match usage(&ident.to_string()) {
+                Some(usage) => Diagnostic::from_parse_error(
+                    &e,
+                    ident.span(),
+                    format!("invalid `{}` argument, expected {}", ident, usage),
+                ),
+                None => Diagnostic::new(
+                    ident.span(),
+                    format!(
+                        "unknown {} argument `{}`, expected one of {}",
+                        attribute, ident, expected
+                    ),
+                ),
+            },
+            _ => Diagnostic::new(
+                first_span(tokens),
+                format!("expected an {} argument, one of {}", attribute, expected),
+            ),
+        })
+}
+
+fn parse_simple_function(input: &mut TokenIter) -> Result<SimpleFunction, Diagnostic> {
+    let item_span = input
+        .clone()
+        .next()
+        .map_or_else(Span::call_site, |token| token.span());
     match input.parse::<FnSig>() {
         Ok(parsed) => {
             // Handle attributes
@@ -189,11 +580,18 @@ fn parse_simple_function(input: &mut TokenIter) -> Result<SimpleFunction, String
                 body,
             })
         }
-        Err(e) => Err(format!("Failed to parse function: {}", e)),
+        Err(e) => Err(Diagnostic::from_parse_error(
+            &e,
+            item_span,
+            "`#[instrument]` can only be applied to functions with a body",
+        )),
     }
 }
 
-fn generate_instrumented_function(args: InstrumentArgs, func: SimpleFunction) -> TokenStream {
+fn generate_instrumented_function(
+    args: InstrumentArgs,
+    func: SimpleFunction,
+) -> Result<TokenStream, Diagnostic> {
     let SimpleFunction {
         attrs,
         vis,
@@ -209,25 +607,60 @@ fn generate_instrumented_function(args: InstrumentArgs, func: SimpleFunction) ->
         body,
     } = func;
 
+    // `future` instruments the future a sync fn returns, so it must return one
+    let returned_future = match args.future {
+        Some(future_span) => Some(returned_future(
+            future_span,
+            async_kw.is_some(),
+            ret_type.as_ref(),
+        )?),
+        None => None,
+    };
+
+    // Path to the `tracing` crate in generated code, `crustrace`'s re-export unless overridden
+    let tracing = args
+        .crate_path
+        .clone()
+        .unwrap_or_else(|| quote!(::crustrace::__private::tracing));
+
+    // Extract parameter fields, followed by any custom fields
+    let param_fields = extract_param_fields(&params, &args);
+    let ret_fallback = args.ret_fallback && args.ret_args.is_some();
+    let record_fallback = (ret_fallback
+        || recorded_params(&params, &args)
+            .iter()
+            .any(|(_, record)| *record == RecordType::Auto))
+    .then(|| record_fallback_items(&tracing));
+    let sig_params = strip_record_attrs(&params);
+    let custom_fields = args
+        .fields
+        .iter()
+        .flatten()
+        .map(|field| field.to_field_tokens(&tracing));
+    let qualified_field = args
+        .qualified_name
+        .as_ref()
+        .map(|qualified| quote!(, code.function.name = #qualified));
+
     // Determine span name
     let span_name = args.name.unwrap_or_else(|| fn_name.to_string());
 
     // Determine function level
-    let function_level = match args.level.as_deref() {
-        Some("trace") => quote!(tracing::Level::TRACE),
-        Some("debug") => quote!(tracing::Level::DEBUG),
-        Some("info") => quote!(tracing::Level::INFO),
-        Some("warn") => quote!(tracing::Level::WARN),
-        Some("error") => quote!(tracing::Level::ERROR),
-        _ => quote!(tracing::Level::INFO),
+    let function_level = match &args.level {
+        Some(level_arg) => resolve_level(&level_arg.value)?,
+        None => Level::Info,
     };
 
-    // Extract parameter fields
-    let param_fields = extract_param_fields(&params);
-
     // Generate tokens for all the modifiers
     let vis_tokens = vis.unwrap_or_default();
     let const_tokens = const_kw.unwrap_or_default();
+    let is_async = async_kw.is_some();
+    // A sync fn returning `Box::pin(async move { ... })`, as `#[async_trait]` desugars to
+    let boxed = if is_async || returned_future.is_some() {
+        None
+    } else {
+        boxed_async_body(&body)
+    };
     let async_tokens = async_kw.unwrap_or_default();
     let unsafe_tokens = unsafe_kw.unwrap_or_default();
     let extern_tokens = extern_kw.unwrap_or_default();
@@ -235,21 +668,47 @@ fn generate_instrumented_function(args: InstrumentArgs, func: SimpleFunction) ->
     let ret_tokens = ret_type.unwrap_or_default();
     let where_tokens = where_clause.unwrap_or_default();
 
-    // Generate the body handling based on whether ret is enabled
-    let body_handling = if let Some(ret_args) = args.ret_args {
-        // Determine the level for the ret event
-        let ret_level = if let Some(level_arg) = ret_args.custom_level() {
-            match level_arg.value.as_str() {
-                "trace" => quote!(tracing::Level::TRACE),
-                "debug" => quote!(tracing::Level::DEBUG),
-                "info" => quote!(tracing::Level::INFO),
-                "warn" => quote!(tracing::Level::WARN),
-                "error" => quote!(tracing::Level::ERROR),
-                _ => function_level.clone(),
-            }
-        } else {
-            function_level.clone()
-        };
+    // Levels for the ret and err events, which may be overridden in their own arguments
+    let ret_level = match args.ret_args.as_ref().and_then(RetArgs::custom_level) {
+        Some(level_arg) => resolve_level(&level_arg.value)?,
+        None => function_level,
+    };
+    // Errors are reported at ERROR unless overridden, regardless of the span level
+    let err_level = match args.err_args.as_ref().and_then(ErrArgs::custom_level) {
+        Some(level_arg) => resolve_level(&level_arg.value)?,
+        None => Level::Error,
+    };
+
+    // With `ret(record)`, the return value is recorded on the span rather than as an event. The
+    // span of a future is moved into it, so its body records on a clone of the span instead.
+    let record_ret = args.ret_args.as_ref().is_some_and(RetArgs::records);
+    let records_in_future = is_async || boxed.is_some() || returned_future.is_some();
+    let record_span = if records_in_future {
+        quote!(__tracing_attr_record_span)
+    } else {
+        quote!(__tracing_attr_span)
+    };
+    let return_value_field = record_ret.then(|| quote!(, return_value = #tracing::field::Empty));
+    let record_span_clone = (record_ret && records_in_future)
+        .then(|| quote!(let __tracing_attr_record_span = __tracing_attr_span.clone();));
+
+    // Build the return value event (or recording) if ret is enabled
+    let ret_event = args.ret_args.map(|ret_args| {
+        if ret_fallback {
+            let value = quote!((&&&__TracingAttrField(&__tracing_attr_ret)).__tracing_attr_record());
+            return if record_ret {
+                quote!(#record_span.record("return_value", #value))
+            } else {
+                quote!(#tracing::event!(#tracing::Level::#ret_level, return_value = #value))
+            };
+        }
+        if record_ret {
+            let formatter = match ret_args.format_mode() {
+                crate::parse::FormatMode::Display => quote!(display),
+                crate::parse::FormatMode::Debug => quote!(debug),
+            };
+            return quote!(#record_span.record("return_value", #tracing::field::#formatter(&__tracing_attr_ret)));
+        }
 
         // Determine the format mode
         let format_token = match ret_args.format_mode() {
@@ -257,15 +716,122 @@ fn generate_instrumented_function(args: InstrumentArgs, func: SimpleFunction) ->
             crate::parse::FormatMode::Debug => quote!(?),
         };
 
-        quote! {
-            let __tracing_attr_ret = (|| #body)();
-            tracing::event!(#ret_level, return_value = #format_token __tracing_attr_ret);
-            __tracing_attr_ret
-        }
+        quote!(#tracing::event!(#tracing::Level::#ret_level, return_value = #format_token __tracing_attr_ret))
+    });
+
+    // Build the error event if err is enabled
+    let err_event = args.err_args.map(|err_args| {
+        // Determine the format mode
+        let format_token = match err_args.format_mode() {
+            crate::parse::FormatMode::Display => quote!(%),
+            crate::parse::FormatMode::Debug => quote!(?),
+        };
+
+        quote!(#tracing::event!(#tracing::Level::#err_level, error = #format_token __tracing_attr_err))
+    });
+
+    // To capture the return value, including early `return`s and `?`, the body runs on its own:
+    // an async body as an inner future (a closure can't `.await`), a sync body as a closure,
+    // and with `future` the returned future is awaited for its output.
+    // The closure is called through an `FnOnce` bound so it can return borrows of its captures
+    // (e.g. `&mut self.field`), which an immediately-called `FnMut` closure could not.
+    let async_block = if is_async {
+        Some(quote!(async move #body))
+    } else if returned_future.is_some() {
+        Some(quote!(__tracing_attr_future))
     } else {
-        body
+        boxed.as_ref().map(|boxed| boxed.future.clone())
+    };
+    let run_body = match &async_block {
+        Some(async_block) => quote!(#async_block.await),
+        None => quote!(__tracing_attr_call_once(move || #body)),
+    };
+
+    // Generate the body handling based on whether ret and/or err are enabled
+    let wraps_body = err_event.is_some() || ret_event.is_some();
+    let body_handling = match (err_event, ret_event) {
+        (Some(err_event), Some(ret_event)) => quote! {
+            match #run_body {
+                Ok(__tracing_attr_ret) => {
+                    #ret_event;
+                    Ok(__tracing_attr_ret)
+                }
+                Err(__tracing_attr_err) => {
+                    #err_event;
+                    Err(__tracing_attr_err)
+                }
+            }
+        },
+        (Some(err_event), None) => quote! {
+            match #run_body {
+                Ok(__tracing_attr_ret) => Ok(__tracing_attr_ret),
+                Err(__tracing_attr_err) => {
+                    #err_event;
+                    Err(__tracing_attr_err)
+                }
+            }
+        },
+        (None, Some(ret_event)) => quote! {
+            let __tracing_attr_ret = #run_body;
+            #ret_event;
+            __tracing_attr_ret
+        },
+        (None, None) => body.clone(),
     };
 
+    // `events(enter)` emits an event with the parameters when the function is called, while
+    // `events(exit)` and `timing` run when a guard is dropped, so they happen however the
+    // function returns, including by `?` or a panic. A future's guard is moved into it, to be
+    // dropped when it completes (or is dropped itself).
+    let events = args.events.unwrap_or_default();
+    let enter_event = events.enter.then(|| {
+        let message = format!("enter {span_name}");
+        quote! {
+            #tracing::event!(parent: &__tracing_attr_span, #tracing::Level::#function_level #param_fields, #message);
+        }
+    });
+    let has_exit_guard = events.exit || args.timing;
+    let elapsed_field = args
+        .timing
+        .then(|| quote!(, elapsed_us = #tracing::field::Empty));
+    let exit_guard = has_exit_guard.then(|| {
+        let start_field = args
+            .timing
+            .then(|| quote!(start: ::std::time::Instant,));
+        let start = args
+            .timing
+            .then(|| quote!(start: ::std::time::Instant::now(),));
+        let record_elapsed = args.timing.then(|| {
+            quote! {
+                let elapsed_us = self.start.elapsed().as_micros() as u64;
+                self.span.record("elapsed_us", elapsed_us);
+            }
+        });
+        let exit_event = events.exit.then(|| {
+            let message = format!("exit {span_name}");
+            let elapsed = args.timing.then(|| quote!(elapsed_us,));
+            quote! {
+                #tracing::event!(parent: &self.span, #tracing::Level::#function_level, #elapsed #message);
+            }
+        });
+        quote! {
+            struct __TracingAttrExit {
+                span: #tracing::Span,
+                #start_field
+            }
+            impl ::core::ops::Drop for __TracingAttrExit {
+                fn drop(&mut self) {
+                    #record_elapsed
+                    #exit_event
+                }
+            }
+            let __tracing_attr_exit = __TracingAttrExit {
+                span: __tracing_attr_span.clone(),
+                #start
+            };
+        }
+    });
+
     // Target handling - only include if explicitly provided
     let target_tokens = if let Some(target) = &args.target {
         quote!(target: #target,)
@@ -278,71 +844,420 @@ fn generate_instrumented_function(args: InstrumentArgs, func: SimpleFunction) ->
     } else {
         quote!()
     };
+    // Each follows_from cause is linked to the span as soon as it is created
+    let follows_from_tokens = args.follows_from.as_ref().map(|follows_from| {
+        quote! {
+            for __tracing_attr_cause in #follows_from {
+                __tracing_attr_span.follows_from(__tracing_attr_cause);
+            }
+        }
+    });
+
+    let call_once = quote! {
+        #[inline(always)]
+        fn __tracing_attr_call_once<R>(f: impl FnOnce() -> R) -> R {
+            f()
+        }
+    };
+
+    // Entering the span with a guard would hold it across `.await` points, so async bodies
+    // become a future instrumented with the span instead, which a boxed future is boxed after.
+    // With `future`, the span is entered while the body builds the future it returns.
+    let instrumented_body = if let Some(async_block) = async_block {
+        let future = match (wraps_body, has_exit_guard) {
+            (false, false) => async_block,
+            (true, false) => quote!(async move { #body_handling }),
+            (false, true) => quote! {
+                async move {
+                    let __tracing_attr_exit = __tracing_attr_exit;
+                    #run_body
+                }
+            },
+            (true, true) => quote! {
+                async move {
+                    let __tracing_attr_exit = __tracing_attr_exit;
+                    #body_handling
+                }
+            },
+        };
+        let instrumented = quote!(#tracing::Instrument::instrument(#future, __tracing_attr_span));
+        match (boxed, returned_future) {
+            (
+                Some(BoxedAsync {
+                    statements,
+                    box_pin,
+                    ..
+                }),
+                _,
+            ) => quote! {
+                #statements
+                #box_pin(#instrumented)
+            },
+            (None, Some(returned_future)) => {
+                let returned = match returned_future {
+                    ReturnedFuture::Impl => instrumented,
+                    ReturnedFuture::Boxed => quote!(::std::boxed::Box::pin(#instrumented)),
+                };
+                quote! {
+                    let __tracing_attr_future = {
+                        let __tracing_attr_guard = __tracing_attr_span.enter();
+                        #call_once
+
+                        __tracing_attr_call_once(move || #body)
+                    };
+                    #returned
+                }
+            }
+            (None, None) => quote!(#instrumented.await),
+        }
+    } else {
+        let call_once = wraps_body.then_some(call_once);
+        quote! {
+            let __tracing_attr_guard = __tracing_attr_span.enter();
+            #call_once
+
+            #body_handling
+        }
+    };
 
     // Generate the instrumented function
-    quote! {
+    Ok(quote! {
         #(#attrs)*
-        #vis_tokens #const_tokens #async_tokens #unsafe_tokens #extern_tokens fn #fn_name #generics_tokens #params #ret_tokens #where_tokens {
-            let __tracing_attr_span = tracing::span!(
+        #vis_tokens #const_tokens #async_tokens #unsafe_tokens #extern_tokens fn #fn_name #generics_tokens #sig_params #ret_tokens #where_tokens {
+            #record_fallback
+            let __tracing_attr_span = #tracing::span!(
                 #target_tokens
                 #parent_tokens
-                #function_level,
+                #tracing::Level::#function_level,
                 #span_name
                 #param_fields
+                #(, #custom_fields)*
+                #return_value_field
+                #elapsed_field
+                #qualified_field
             );
-            let __tracing_attr_guard = __tracing_attr_span.enter();
-
-            #body_handling
+            #follows_from_tokens
+            #record_span_clone
+            #enter_event
+            #exit_guard
+            #instrumented_body
         }
+    })
+}
+
+/// Whether a function's return type is a `Result`
+fn returns_result(ret_type: Option<&TokenStream>) -> bool {
+    ret_type
+        .and_then(|ret_type| ret_type.to_token_iter().parse_all::<ReturnType>().ok())
+        .is_some_and(|ret_type| ret_type.is_result())
+}
+
+/// The kind of future a function with the `future` argument returns, erroring if it is async or
+/// returns anything else
+fn returned_future(
+    future_span: Span,
+    is_async: bool,
+    ret_type: Option<&TokenStream>,
+) -> Result<ReturnedFuture, Diagnostic> {
+    if is_async {
+        return Err(Diagnostic::new(
+            future_span,
+            "`future` is for a sync fn returning a future, an `async fn` is instrumented as one \
+             already",
+        ));
     }
+    ret_type
+        .and_then(|ret_type| ret_type.to_token_iter().parse_all::<ReturnType>().ok())
+        .and_then(|ret_type| ret_type.future_kind())
+        .ok_or_else(|| {
+            Diagnostic::new(
+                ret_type.map_or(future_span, first_span),
+                "`future` requires the function to return `impl Future<Output = ...>` or \
+                 `Pin<Box<dyn Future<Output = ...>>>`",
+            )
+        })
 }
 
-/// Extract parameter names from function parameters for tracing fields
-fn extract_param_fields(params: &TokenStream) -> TokenStream {
-    // Parse the params TokenStream to extract the parameter list
-    let mut param_iter = params.clone().into_token_iter();
+/// A function body ending in `Box::pin(async move { ... })`
+struct BoxedAsync {
+    /// Statements before the boxed future
+    statements: TokenStream,
+    /// Path to `Box::pin` as written
+    box_pin: TokenStream,
+    /// The `async move { ... }` block
+    future: TokenStream,
+}
 
-    // Try to parse as ParenthesisGroupContaining<Option<CommaDelimitedVec<FnParam>>>
-    let parsed_params = match param_iter
-        .parse::<ParenthesisGroupContaining<Option<CommaDelimitedVec<FnParam>>>>()
-    {
-        Ok(params) => params,
-        Err(_) => return quote!(), // Parse failed, return empty
+/// Split a function body whose tail expression is `Box::pin(async move { ... })`, the shape
+/// `#[async_trait]` (and hand-written boxed futures) give a sync fn returning a future
+fn boxed_async_body(body: &TokenStream) -> Option<BoxedAsync> {
+    let Some(proc_macro2::TokenTree::Group(group)) = body.clone().into_iter().next() else {
+        return None;
     };
+    let tokens: Vec<_> = group.stream().into_iter().collect();
+
+    // The tail expression follows the last top-level `;`
+    let tail_start = tokens
+        .iter()
+        .rposition(
+            |token| matches!(token, proc_macro2::TokenTree::Punct(punct) if punct.as_char() == ';'),
+        )
+        .map_or(0, |index| index + 1);
+    let tail: TokenStream = tokens[tail_start..].iter().cloned().collect();
+    let call = tail.to_token_iter().parse_all::<BoxPinCall>().ok()?;
+    if !call.is_box_pin() {
+        return None;
+    }
 
-    let Some(param_list) = &parsed_params.content else {
-        return quote!(); // No parameters
-    };
+    let mut box_pin = TokenStream::new();
+    unsynn::ToTokens::to_tokens(&call._leading, &mut box_pin);
+    unsynn::ToTokens::to_tokens(&call.path, &mut box_pin);
+    let mut future = TokenStream::new();
+    unsynn::ToTokens::to_tokens(&call.future.content, &mut future);
+    Some(BoxedAsync {
+        statements: tokens[..tail_start].iter().cloned().collect(),
+        box_pin,
+        future,
+    })
+}
+
+/// Resolve a `level = ...` argument, erroring on anything that isn't a tracing level
+pub(crate) fn resolve_level(value: &LevelValue) -> Result<Level, Diagnostic> {
+    match value.level() {
+        Some(level) => Ok(level),
+        None => Err(Diagnostic::new(
+            value.span(),
+            "unknown tracing level, expected one of trace, debug, info, warn, error or 1-5",
+        )),
+    }
+}
+
+/// Parse the function parameter list, or `None` if it isn't one we understand
+fn parse_params(params: &TokenStream) -> Option<Vec<FnParam>> {
+    let mut param_iter = params.clone().into_token_iter();
+    let parsed_params = param_iter
+        .parse::<ParenthesisGroupContaining<Option<CommaDelimitedVec<FnParam>>>>()
+        .ok()?;
+
+    Some(
+        parsed_params
+            .content
+            .map(|list| list.0.into_iter().map(|param| param.value).collect())
+            .unwrap_or_default(),
+    )
+}
+
+/// The strategy requested by a `#[record(...)]` attribute, if there is a valid one
+fn record_override(attributes: &Option<Many<Attribute>>) -> Option<RecordType> {
+    attributes
+        .iter()
+        .flat_map(|attrs| &attrs.0)
+        .filter(|attr| attr.value.is_record())
+        .find_map(|attr| attr.value.parse_record().ok())
+        .map(|record| record.record_type())
+}
 
-    let mut fields = Vec::new();
+/// Collect the identifiers bound by the function parameters, each with how it is recorded
+/// (`self` is never included)
+fn param_bindings(params: &TokenStream) -> Vec<(Ident, RecordType)> {
+    let mut bindings = Vec::new();
 
-    for param in &param_list.0 {
-        match &param.value {
+    for param in parse_params(params).unwrap_or_default() {
+        match &param {
             FnParam::Named(named_param) => {
-                let param_name = &named_param.name;
-                #[cfg(feature = "debug")]
-                fields.push(quote!(, #param_name = ?#param_name));
-                #[cfg(not(feature = "debug"))]
-                fields.push(quote!(, #param_name = #param_name));
+                let record = record_override(&named_param.attributes).unwrap_or_else(|| {
+                    let mut param_type = TokenStream::new();
+                    unsynn::ToTokens::to_tokens(&named_param.param_type, &mut param_type);
+                    RecordType::from_type(&param_type)
+                });
+                bindings.push((named_param.name.clone(), record));
             }
             FnParam::SelfParam(_) => {
                 // Skip self parameters for tracing
             }
             FnParam::Pattern(pattern_param) => {
+                let record = record_override(&pattern_param.attributes).unwrap_or(RecordType::Auto);
                 let identifiers = pattern_param.pattern.extract_identifiers();
-                for ident in identifiers {
-                    #[cfg(feature = "debug")]
-                    fields.push(quote!(, #ident = ?#ident));
-                    #[cfg(not(feature = "debug"))]
-                    fields.push(quote!(, #ident = #ident));
+                bindings.extend(identifiers.into_iter().map(|ident| (ident.clone(), record)));
+            }
+        }
+    }
+
+    bindings
+}
+
+/// Collect the identifiers bound by the function parameters (`self` is never included)
+fn param_idents(params: &TokenStream) -> Vec<Ident> {
+    param_bindings(params)
+        .into_iter()
+        .map(|(ident, _)| ident)
+        .collect()
+}
+
+/// Check that every `#[record(...)]` parameter attribute names a recording strategy
+fn validate_record_attrs(params: &TokenStream) -> Result<(), Diagnostic> {
+    for param in parse_params(params).unwrap_or_default() {
+        let attributes = match &param {
+            FnParam::Named(named_param) => &named_param.attributes,
+            FnParam::Pattern(pattern_param) => &pattern_param.attributes,
+            FnParam::SelfParam(_) => continue,
+        };
+        for attr in attributes.iter().flat_map(|attrs| &attrs.0) {
+            if attr.value.is_record() {
+                attr.value.parse_record().map_err(|e| {
+                    Diagnostic::from_parse_error(
+                        &e,
+                        attr.value.content.0.span(),
+                        "invalid `record` attribute, expected `#[record(Value | Debug | Display)]`",
+                    )
+                })?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The parameter list with `#[record(...)]` attributes removed, for the emitted signature
+fn strip_record_attrs(params: &TokenStream) -> TokenStream {
+    let mut tokens = params.clone().into_iter();
+    let Some(proc_macro2::TokenTree::Group(group)) = tokens.next() else {
+        return params.clone();
+    };
+
+    let mut inner = Vec::new();
+    let mut group_tokens = group.stream().into_iter().peekable();
+    while let Some(token) = group_tokens.next() {
+        if let proc_macro2::TokenTree::Punct(pound) = &token {
+            if pound.as_char() == '#' {
+                if let Some(proc_macro2::TokenTree::Group(attr)) = group_tokens.peek() {
+                    let is_record = matches!(
+                        attr.stream().into_iter().next(),
+                        Some(proc_macro2::TokenTree::Ident(ident)) if ident == "record"
+                    );
+                    if is_record {
+                        group_tokens.next();
+                        continue;
+                    }
                 }
             }
         }
+        inner.push(token);
     }
 
+    let mut stripped = proc_macro2::Group::new(group.delimiter(), inner.into_iter().collect());
+    stripped.set_span(group.span());
+    let mut result: TokenStream = proc_macro2::TokenTree::Group(stripped).into();
+    result.extend(tokens);
+    result
+}
+
+/// Check that every parameter named in `skip(...)` exists, erroring on the first that doesn't
+fn validate_skips(args: &InstrumentArgs, params: &TokenStream) -> Result<(), Diagnostic> {
+    let Some(skips) = &args.skips else {
+        return Ok(());
+    };
+
+    let idents = param_idents(params);
+    for skip in skips {
+        // `self` is never recorded, but skipping it explicitly is harmless
+        if skip != "self" && !idents.contains(skip) {
+            return Err(Diagnostic::new(
+                skip.span(),
+                "attempting to skip non-existent parameter",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// The parameters recorded as span fields, and how
+fn recorded_params(params: &TokenStream, args: &InstrumentArgs) -> Vec<(Ident, RecordType)> {
+    if args.skip_all {
+        return Vec::new();
+    }
+
+    param_bindings(params)
+        .into_iter()
+        .filter(|(ident, _)| !args.skips.iter().flatten().any(|skip| skip == ident))
+        // A custom field of the same name replaces the auto-captured parameter
+        .filter(|(ident, _)| {
+            !args
+                .fields
+                .iter()
+                .flatten()
+                .any(|field| field.single_name() == Some(ident))
+        })
+        .collect()
+}
+
+/// Extract parameter names from function parameters for tracing fields
+fn extract_param_fields(params: &TokenStream, args: &InstrumentArgs) -> TokenStream {
+    let fields = recorded_params(params, args)
+        .into_iter()
+        .map(|(ident, record)| match record {
+            RecordType::Value => quote!(, #ident = #ident),
+            RecordType::Debug => quote!(, #ident = ?#ident),
+            RecordType::Display => quote!(, #ident = %#ident),
+            // Types that are neither `Value` nor `Debug` are still recorded, by type name
+            RecordType::Auto => {
+                quote!(, #ident = (&&&__TracingAttrField(&#ident)).__tracing_attr_record())
+            }
+        });
+
     quote!(#(#fields)*)
 }
 
+/// Items for recording parameters (and `#[omni]`'s return values) whose type isn't known to be a
+/// `Value` or `Debug`, emitted in each function that has any, so they only need the `tracing`
+/// crate.
+///
+/// `(&&&__TracingAttrField(&x)).__tracing_attr_record()` resolves by autoref specialization:
+/// method resolution tries each receiver in turn, removing one reference at a time, so the
+/// first of these that applies wins:
+///
+/// 1. On `&&__TracingAttrField<T>`, for `T: tracing::Value`: recorded as the value itself
+/// 2. On `&__TracingAttrField<T>`, for `T: Debug`: recorded with `Debug`
+/// 3. On `__TracingAttrField<T>`, for any `T`: recorded as the name of `T`
+fn record_fallback_items(tracing: &TokenStream) -> TokenStream {
+    quote! {
+        #[allow(dead_code)]
+        struct __TracingAttrField<'a, T: ?Sized>(&'a T);
+        #[allow(dead_code)]
+        trait __TracingAttrRecordValue<'a, T: #tracing::field::Value + ?Sized> {
+            fn __tracing_attr_record(&self) -> &'a T;
+        }
+        impl<'a, T: #tracing::field::Value + ?Sized> __TracingAttrRecordValue<'a, T>
+            for &&__TracingAttrField<'a, T>
+        {
+            fn __tracing_attr_record(&self) -> &'a T {
+                self.0
+            }
+        }
+        #[allow(dead_code)]
+        trait __TracingAttrRecordDebug<'a, T: ::core::fmt::Debug + ?Sized> {
+            fn __tracing_attr_record(&self) -> #tracing::field::DebugValue<&'a T>;
+        }
+        impl<'a, T: ::core::fmt::Debug + ?Sized> __TracingAttrRecordDebug<'a, T>
+            for &__TracingAttrField<'a, T>
+        {
+            fn __tracing_attr_record(&self) -> #tracing::field::DebugValue<&'a T> {
+                #tracing::field::debug(self.0)
+            }
+        }
+        #[allow(dead_code)]
+        trait __TracingAttrRecordTypeName {
+            fn __tracing_attr_record(&self) -> &'static str;
+        }
+        impl<T: ?Sized> __TracingAttrRecordTypeName for __TracingAttrField<'_, T> {
+            fn __tracing_attr_record(&self) -> &'static str {
+                ::core::any::type_name::<T>()
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -460,14 +1375,44 @@ mod tests {
             }
         };
 
+        let error = instrument_impl(args, item).expect_err("A const fn can't create a span");
+        assert!(error
+            .to_string()
+            .contains("`#[instrument]` can't be applied to a `const fn`"));
+    }
+
+    #[test]
+    fn test_const_function_with_twin() {
+        let args = quote!(const_twin = test_const_twin);
+        let item = quote! {
+            pub const fn test_const(#[record(Debug)] x: u32) -> u32 {
+                x * 2
+            }
+        };
+
         let result = instrument_impl(args, item);
         assert!(result.is_ok());
 
         let output = result.unwrap();
         let output_str = format_and_print(output);
 
-        assert!(output_str.contains("const fn test_const"));
-        assert!(output_str.contains("tracing::span!"));
+        assert!(output_str.contains("pub const fn test_const_twin(x: u32) -> u32"));
+        assert!(output_str.contains("pub fn test_const(x: u32) -> u32"));
+        assert!(!output_str.contains("const fn test_const("));
+        assert!(output_str.contains("__tracing_attr_span"));
+    }
+
+    #[test]
+    fn test_const_twin_on_non_const_function() {
+        let args = quote!(const_twin = twin);
+        let item = quote! {
+            fn not_const() {}
+        };
+
+        let error = instrument_impl(args, item).expect_err("const_twin needs a const fn");
+        assert!(error
+            .to_string()
+            .contains("`const_twin` only applies to a `const fn`"));
     }
 
     #[test]
@@ -4,7 +4,7 @@ use quote::quote;
 #[test]
 fn test_no_parameters() {
     let params = quote! { () };
-    let result = extract_param_fields(&params);
+    let result = extract_param_fields(&params, &InstrumentArgs::default());
 
     println!("No params input: {}", params);
     println!("No params output: {}", result);
@@ -16,7 +16,7 @@ fn test_no_parameters() {
 #[test]
 fn test_single_parameter() {
     let params = quote! { (x: i32) };
-    let result = extract_param_fields(&params);
+    let result = extract_param_fields(&params, &InstrumentArgs::default());
 
     println!("Single param input: {}", params);
     println!("Single param output: {}", result);
@@ -32,7 +32,7 @@ fn test_single_parameter() {
 #[test]
 fn test_multiple_parameters() {
     let params = quote! { (name: &str, count: usize) };
-    let result = extract_param_fields(&params);
+    let result = extract_param_fields(&params, &InstrumentArgs::default());
 
     println!("Multiple params input: {}", params);
     println!("Multiple params output: {}", result);
@@ -51,14 +51,15 @@ fn test_multiple_parameters() {
 #[test]
 fn test_mut_parameter() {
     let params = quote! { (mut data: Vec<u8>) };
-    let result = extract_param_fields(&params);
+    let result = extract_param_fields(&params, &InstrumentArgs::default());
 
     println!("Mut param input: {}", params);
     println!("Mut param output: {}", result);
 
     let result_str = result.to_string();
     assert!(
-        result_str.contains(", data = data"),
+        result_str
+            .contains(", data = (&& & __TracingAttrField (& data)) . __tracing_attr_record ()"),
         "Should extract parameter name 'data' despite 'mut' keyword"
     );
 }
@@ -66,7 +67,7 @@ fn test_mut_parameter() {
 #[test]
 fn test_self_parameter_skipped() {
     let params = quote! { (&self, value: i32) };
-    let result = extract_param_fields(&params);
+    let result = extract_param_fields(&params, &InstrumentArgs::default());
 
     println!("Self param input: {}", params);
     println!("Self param output: {}", result);
@@ -86,7 +87,7 @@ fn test_self_parameter_skipped() {
 #[test]
 fn test_mut_self_parameter_skipped() {
     let params = quote! { (&mut self, new_value: String) };
-    let result = extract_param_fields(&params);
+    let result = extract_param_fields(&params, &InstrumentArgs::default());
 
     println!("Mut self param input: {}", params);
     println!("Mut self param output: {}", result);
@@ -106,18 +107,21 @@ fn test_mut_self_parameter_skipped() {
 #[test]
 fn test_complex_types() {
     let params = quote! { (callback: fn(i32) -> String, data: Option<Vec<T>>) };
-    let result = extract_param_fields(&params);
+    let result = extract_param_fields(&params, &InstrumentArgs::default());
 
     println!("Complex types input: {}", params);
     println!("Complex types output: {}", result);
 
     let result_str = result.to_string();
     assert!(
-        result_str.contains(", callback = callback"),
+        result_str.contains(
+            ", callback = (&& & __TracingAttrField (& callback)) . __tracing_attr_record ()"
+        ),
         "Should handle function pointer types"
     );
     assert!(
-        result_str.contains(", data = data"),
+        result_str
+            .contains(", data = (&& & __TracingAttrField (& data)) . __tracing_attr_record ()"),
         "Should handle complex generic types"
     );
 }
@@ -125,18 +129,20 @@ fn test_complex_types() {
 #[test]
 fn test_generic_parameter() {
     let params = quote! { (value: T, other: Option<U>) };
-    let result = extract_param_fields(&params);
+    let result = extract_param_fields(&params, &InstrumentArgs::default());
 
     println!("Generic param input: {}", params);
     println!("Generic param output: {}", result);
 
     let result_str = result.to_string();
     assert!(
-        result_str.contains(", value = value"),
+        result_str
+            .contains(", value = (&& & __TracingAttrField (& value)) . __tracing_attr_record ()"),
         "Should handle generic type T"
     );
     assert!(
-        result_str.contains(", other = other"),
+        result_str
+            .contains(", other = (&& & __TracingAttrField (& other)) . __tracing_attr_record ()"),
         "Should handle generic type Option<U>"
     );
 }
@@ -144,7 +150,7 @@ fn test_generic_parameter() {
 #[test]
 fn test_mixed_parameters() {
     let params = quote! { (&self, mut count: usize, name: &str, callback: impl Fn()) };
-    let result = extract_param_fields(&params);
+    let result = extract_param_fields(&params, &InstrumentArgs::default());
 
     println!("Mixed params input: {}", params);
     println!("Mixed params output: {}", result);
@@ -160,7 +166,9 @@ fn test_mixed_parameters() {
         "Should extract 'name' reference parameter"
     );
     assert!(
-        result_str.contains(", callback = callback"),
+        result_str.contains(
+            ", callback = (&& & __TracingAttrField (& callback)) . __tracing_attr_record ()"
+        ),
         "Should extract 'callback' impl parameter"
     );
     assert!(!result_str.contains("self"), "Should skip 'self' parameter");
@@ -169,56 +177,59 @@ fn test_mixed_parameters() {
 #[test]
 fn test_reference_parameters() {
     let params = quote! { (data: &[u8], text: &mut String) };
-    let result = extract_param_fields(&params);
+    let result = extract_param_fields(&params, &InstrumentArgs::default());
 
     println!("Reference params input: {}", params);
     println!("Reference params output: {}", result);
 
     let result_str = result.to_string();
     assert!(
-        result_str.contains(", data = data"),
-        "Should handle &[u8] slice reference"
+        result_str
+            .contains(", data = (&& & __TracingAttrField (& data)) . __tracing_attr_record ()"),
+        "Should record &[u8] through the fallback"
     );
     assert!(
         result_str.contains(", text = text"),
-        "Should handle &mut String reference"
+        "Should record &mut String as a value"
     );
 }
 
 #[test]
-fn test_pattern_parameter_skipped() {
-    // Pattern parameters should be skipped (for now)
+fn test_pattern_parameter_identifiers() {
+    // The identifiers bound by a tuple pattern are recorded like parameters
     let params = quote! { ((x, y): (i32, i32)) };
-    let result = extract_param_fields(&params);
+    let result = extract_param_fields(&params, &InstrumentArgs::default());
 
     println!("Pattern param input: {}", params);
     println!("Pattern param output: {}", result);
 
-    // Pattern parameters should be skipped, so result should be empty
     let result_str = result.to_string();
     assert_eq!(
-        result_str.trim(),
-        "",
-        "Pattern parameters should be skipped"
+        result_str,
+        ", x = (&& & __TracingAttrField (& x)) . __tracing_attr_record () \
+         , y = (&& & __TracingAttrField (& y)) . __tracing_attr_record ()"
     );
 }
 
 #[test]
-#[ignore]
 fn test_tuple_destructuring_parameter() {
-    // Test that tuple destructuring works (when implemented)
-    let params = quote! { ((a, b): (i32, i32), c: String) };
-    let result = extract_param_fields(&params);
+    let params = quote! { ((a, b): (i32, i32), c: String, Point(x, y): Point) };
+    let args = InstrumentArgs {
+        skips: Some(vec![Ident::new("b", Span::call_site())]),
+        ..Default::default()
+    };
+    let result = extract_param_fields(&params, &args);
 
     println!("Tuple destructure input: {}", params);
     println!("Tuple destructure output: {}", result);
 
     let result_str = result.to_string();
-    // Should contain c but skip the pattern parameter (a, b)
+    // Every identifier in the tuple and tuple struct patterns is recorded, except skipped ones
+    assert!(result_str.contains(", a = (&& & __TracingAttrField (& a))"));
+    assert!(!result_str.contains("(& b)"));
     assert!(result_str.contains(", c = c"));
-    // Should NOT contain individual pattern components for now
-    assert!(!result_str.contains(", a = a"));
-    assert!(!result_str.contains(", b = b"));
+    assert!(result_str.contains(", x = (&& & __TracingAttrField (& x))"));
+    assert!(result_str.contains(", y = (&& & __TracingAttrField (& y))"));
 }
 
 #[test]
@@ -265,6 +276,140 @@ fn test_parsing_pipeline_debug() {
         }
     }
 
-    let result = extract_param_fields(&params);
+    let result = extract_param_fields(&params, &InstrumentArgs::default());
     println!("Final result: {}", result);
 }
+
+fn skip_args(args: TokenStream) -> InstrumentArgs {
+    let mut iter = args.into_token_iter();
+    parse_instrument_args(&mut iter).expect("Should parse skip args")
+}
+
+#[test]
+fn test_skip_named_parameters() {
+    let params = quote! { (conn: &mut Connection, id: u64, secret: String) };
+    let result = extract_param_fields(&params, &skip_args(quote!(skip(conn, secret))));
+
+    let result_str = result.to_string();
+    assert!(result_str.contains(", id = id"), "Should keep 'id'");
+    assert!(!result_str.contains("conn"), "Should skip 'conn'");
+    assert!(!result_str.contains("secret"), "Should skip 'secret'");
+}
+
+#[test]
+fn test_skip_all_parameters() {
+    let params = quote! { (&self, buf: &mut Vec<u8>, len: usize) };
+    let result = extract_param_fields(&params, &skip_args(quote!(skip_all)));
+
+    assert_eq!(
+        result.to_string().trim(),
+        "",
+        "skip_all should record nothing"
+    );
+}
+
+#[test]
+fn test_skip_pattern_identifier() {
+    let params = quote! { ((left, right): Pair, depth: u32) };
+    let result = extract_param_fields(&params, &skip_args(quote!(skip(right))));
+
+    let result_str = result.to_string();
+    assert!(
+        result_str
+            .contains(", left = (&& & __TracingAttrField (& left)) . __tracing_attr_record ()"),
+        "Should keep 'left'"
+    );
+    assert!(
+        result_str.contains(", depth = depth"),
+        "Should keep 'depth'"
+    );
+    assert!(!result_str.contains("right"), "Should skip 'right'");
+}
+
+#[test]
+fn test_skip_nonexistent_parameter() {
+    let params = quote! { (id: u64) };
+    let args = skip_args(quote!(skip(idd)));
+
+    let err = validate_skips(&args, &params).expect_err("Should reject unknown parameter");
+    assert!(err
+        .to_string()
+        .contains("attempting to skip non-existent parameter"));
+}
+
+#[test]
+fn test_skip_self_is_accepted() {
+    let params = quote! { (&self, id: u64) };
+    let args = skip_args(quote!(skip(self)));
+
+    assert!(validate_skips(&args, &params).is_ok());
+}
+
+#[test]
+fn test_skip_and_skip_all_conflict() {
+    let mut iter = quote!(skip(a), skip_all).into_token_iter();
+    assert!(parse_instrument_args(&mut iter).is_err());
+}
+
+#[test]
+fn test_value_types_recorded_as_values() {
+    let params = quote! {
+        (flag: bool, ratio: f64, label: &'a str, nested: &&str, owned: std::string::String)
+    };
+    let result = extract_param_fields(&params, &InstrumentArgs::default());
+
+    let result_str = result.to_string();
+    for name in ["flag", "ratio", "label", "nested", "owned"] {
+        assert!(
+            result_str.contains(&format!(", {name} = {name}")),
+            "Should record '{name}' as a value but got: {result_str}"
+        );
+    }
+}
+
+#[test]
+fn test_other_types_recorded_by_fallback() {
+    let params = quote! { (path: PathBuf, label: Option<String>, bytes: Box<str>) };
+    let result = extract_param_fields(&params, &InstrumentArgs::default());
+
+    let result_str = result.to_string();
+    for name in ["path", "label", "bytes"] {
+        assert!(
+            result_str.contains(&format!(
+                ", {name} = (&& & __TracingAttrField (& {name})) . __tracing_attr_record ()"
+            )),
+            "Should record '{name}' through the fallback but got: {result_str}"
+        );
+    }
+}
+
+#[test]
+fn test_record_attribute_overrides_type() {
+    let params = quote! {
+        (#[record(Display)] path: PathBuf, #[record(Debug)] id: u64, #[record(Value)] n: Count)
+    };
+    let result = extract_param_fields(&params, &InstrumentArgs::default());
+
+    let result_str = result.to_string();
+    assert!(result_str.contains(", path = % path"), "got: {result_str}");
+    assert!(result_str.contains(", id = ? id"), "got: {result_str}");
+    assert!(result_str.contains(", n = n"), "got: {result_str}");
+}
+
+#[test]
+fn test_record_attribute_stripped_from_signature() {
+    let params = quote! { (#[record(Display)] path: PathBuf, #[allow(unused)] id: u64) };
+
+    assert_eq!(
+        strip_record_attrs(&params).to_string(),
+        quote! { (path: PathBuf, #[allow(unused)] id: u64) }.to_string()
+    );
+}
+
+#[test]
+fn test_invalid_record_attribute() {
+    let params = quote! { (#[record(Pretty)] path: PathBuf) };
+
+    let err = validate_record_attrs(&params).expect_err("Should reject unknown strategy");
+    assert!(err.to_string().contains("invalid `record` attribute"));
+}
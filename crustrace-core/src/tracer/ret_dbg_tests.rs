@@ -65,7 +65,8 @@ fn test_ret_dbg_codegen_custom_level() {
 
     // THIS SHOULD FAIL until custom level is implemented
     assert!(
-        output_str.contains("tracing :: event ! (tracing :: Level :: WARN"),
+        output_str
+            .contains("tracing :: event ! (:: crustrace :: __private :: tracing :: Level :: WARN"),
         "Expected WARN level but got: {}",
         output_str
     );
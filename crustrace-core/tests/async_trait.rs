@@ -0,0 +1,120 @@
+use crustrace_core::{instrument_impl, trace_all_impl};
+use insta::assert_snapshot;
+use proc_macro2::TokenStream;
+use quote::quote;
+use rust_format::{Formatter, RustFmt};
+
+fn format(output: TokenStream) -> String {
+    let fmt_str = RustFmt::default()
+        .format_tokens(output)
+        .unwrap_or_else(|e| panic!("Format error: {}", e));
+    println!("Formatted: {}", fmt_str);
+    fmt_str
+}
+
+fn apply_instrument(args: TokenStream, input: TokenStream) -> String {
+    format(instrument_impl(args, input).expect("Should instrument successfully"))
+}
+
+fn apply_trace_all(input: TokenStream) -> String {
+    format(trace_all_impl(TokenStream::new(), input).expect("Should trace successfully"))
+}
+
+/// A method as `#[async_trait]` desugars `async fn get(&self, key: u32) -> Option<String>`
+fn desugared_method() -> TokenStream {
+    quote! {
+        fn get<'life0, 'async_trait>(
+            &'life0 self,
+            key: u32,
+        ) -> ::core::pin::Pin<Box<dyn ::core::future::Future<Output = Option<String>> + ::core::marker::Send + 'async_trait>>
+        where
+            'life0: 'async_trait,
+            Self: 'async_trait,
+        {
+            Box::pin(async move {
+                if let ::core::option::Option::Some(__ret) = ::core::option::Option::None::<Option<String>> {
+                    return __ret;
+                }
+                let __self = self;
+                let key = key;
+                let __ret: Option<String> = { __self.lookup(key).await };
+                #[allow(unreachable_code)]
+                __ret
+            })
+        }
+    }
+}
+
+#[test]
+fn test_boxed_async_body_is_instrumented_inside_the_future() {
+    assert_snapshot!(apply_instrument(quote!(), desugared_method()));
+}
+
+#[test]
+fn test_boxed_async_body_with_ret() {
+    assert_snapshot!(apply_instrument(quote!(ret), desugared_method()));
+}
+
+#[test]
+fn test_boxed_async_body_after_statements() {
+    let item = quote! {
+        fn spawn(&self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+            let client = self.client.clone();
+            ::std::boxed::Box::pin(async move {
+                client.ping().await;
+            })
+        }
+    };
+
+    assert_snapshot!(apply_instrument(quote!(), item));
+}
+
+#[test]
+fn test_box_pin_of_other_futures_is_not_unwrapped() {
+    // Only an async block can be instrumented in place
+    let item = quote! {
+        fn forward(&self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+            Box::pin(self.inner.run())
+        }
+    };
+    let output = apply_instrument(quote!(), item);
+    assert!(output.contains("__tracing_attr_guard"), "got: {output}");
+
+    // Nor is a `Box::pin` that isn't the tail expression
+    let item = quote! {
+        fn run(&self) -> u32 {
+            let _future = Box::pin(async move { 1 });
+            2
+        }
+    };
+    let output = apply_instrument(quote!(), item);
+    assert!(output.contains("__tracing_attr_guard"), "got: {output}");
+}
+
+/// `#[omni]` above `#[async_trait]` sees the `async fn`s before they are desugared
+#[test]
+fn test_omni_before_async_trait() {
+    let input = quote! {
+        #[async_trait]
+        impl Store for Memory {
+            async fn get(&self, key: u32) -> Option<String> {
+                self.lookup(key).await
+            }
+        }
+    };
+
+    assert_snapshot!(apply_trace_all(input));
+}
+
+/// `#[omni]` below `#[async_trait]` sees the desugared methods
+#[test]
+fn test_omni_after_async_trait() {
+    let method = desugared_method();
+    let input = quote! {
+        impl Store for Memory {
+            #method
+        }
+    };
+
+    assert_snapshot!(apply_trace_all(input));
+}
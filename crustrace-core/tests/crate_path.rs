@@ -0,0 +1,55 @@
+use crustrace_core::{instrument_impl, trace_all_impl};
+use insta::assert_snapshot;
+use proc_macro2::TokenStream;
+use quote::quote;
+use rust_format::{Formatter, RustFmt};
+
+fn format(output: TokenStream) -> String {
+    println!("Output: {}", output);
+    let fmt_str = RustFmt::default()
+        .format_tokens(output)
+        .unwrap_or_else(|e| panic!("Format error: {}", e));
+    println!("Formatted: {}", fmt_str);
+    fmt_str
+}
+
+#[test]
+fn test_instrument_crate_path() {
+    let args = quote!(crate = ::tracing, ret, fields(pending));
+    let item = quote! {
+        async fn test_function(x: u32) -> u32 {
+            x + 1
+        }
+    };
+
+    let output = instrument_impl(args, item).expect("Should instrument successfully");
+    assert_snapshot!(format(output));
+}
+
+#[test]
+fn test_omni_crate_path() {
+    let args = quote!(crate = my_reexports::tracing);
+    let input = quote! {
+        fn first(x: u32) -> u32 {
+            x + 1
+        }
+
+        impl Widget {
+            fn second(&self) {}
+        }
+    };
+
+    let output = trace_all_impl(args, input).expect("Should trace successfully");
+    assert_snapshot!(format(output));
+}
+
+#[test]
+fn test_omni_unknown_argument_is_an_error() {
+    let args = quote!(lvl = "debug");
+    let input = quote! {
+        fn first() {}
+    };
+
+    let error = trace_all_impl(args, input).expect_err("Unknown omni argument should error");
+    assert!(error.to_string().contains("unknown omni argument"));
+}
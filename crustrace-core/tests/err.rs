@@ -0,0 +1,88 @@
+use crustrace_core::instrument_impl;
+use insta::assert_snapshot;
+use proc_macro2::TokenStream;
+use quote::quote;
+use rust_format::{Formatter, RustFmt};
+
+fn apply_instrument(args: TokenStream, input: TokenStream) -> String {
+    let output = instrument_impl(args, input).expect("Should instrument successfully");
+    println!("Instrumented: {}", output);
+    let fmt_str = RustFmt::default()
+        .format_tokens(output)
+        .unwrap_or_else(|e| panic!("Format error: {}", e));
+    println!("Formatted: {}", fmt_str);
+    fmt_str
+}
+
+#[test]
+fn test_bare_err() {
+    let args = quote!(err);
+    let item = quote! {
+        fn test_function(x: u32) -> Result<u32, String> {
+            if x > 0 { Ok(x) } else { Err("zero".to_string()) }
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_err_debug_format() {
+    let args = quote!(err(Debug));
+    let item = quote! {
+        fn test_function() -> Result<(), std::io::Error> {
+            Ok(())
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_err_custom_level() {
+    let args = quote!(err(level = "warn"));
+    let item = quote! {
+        fn test_function() -> Result<i32, String> {
+            Ok(42)
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_err_ignores_function_level() {
+    let args = quote!(level = "debug", err);
+    let item = quote! {
+        fn test_function() -> Result<i32, String> {
+            Ok(42)
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_err_with_ret() {
+    let args = quote!(ret, err);
+    let item = quote! {
+        fn test_function(input: &str) -> Result<i32, std::num::ParseIntError> {
+            input.parse::<i32>()
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_err_with_ret_options() {
+    let args = quote!(level = "info", ret(level = "debug", Display), err(Debug));
+    let item = quote! {
+        fn test_function(input: &str) -> Result<i32, std::num::ParseIntError> {
+            let value = input.parse::<i32>()?;
+            Ok(value * 2)
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
@@ -0,0 +1,90 @@
+use crustrace_core::instrument_impl;
+use insta::assert_snapshot;
+use proc_macro2::TokenStream;
+use quote::quote;
+use rust_format::{Formatter, RustFmt};
+
+fn format(output: TokenStream) -> String {
+    let fmt_str = RustFmt::default()
+        .format_tokens(output)
+        .unwrap_or_else(|e| panic!("Format error: {}", e));
+    println!("Formatted: {}", fmt_str);
+    fmt_str
+}
+
+fn apply_instrument(args: TokenStream, input: TokenStream) -> String {
+    format(instrument_impl(args, input).expect("Should instrument successfully"))
+}
+
+fn instrument_error(args: TokenStream, input: TokenStream) -> String {
+    instrument_impl(args, input)
+        .expect_err("Should fail to instrument")
+        .to_string()
+}
+
+fn fallible_function() -> TokenStream {
+    quote! {
+        fn parse_port(input: &str, base: u32) -> Result<u16, std::num::ParseIntError> {
+            let port = u16::from_str_radix(input, base)?;
+            Ok(port)
+        }
+    }
+}
+
+#[test]
+fn test_enter_and_exit_events() {
+    assert_snapshot!(apply_instrument(
+        quote!(events(enter, exit)),
+        fallible_function()
+    ));
+}
+
+#[test]
+fn test_enter_event_only() {
+    let output = apply_instrument(quote!(events(enter), skip(base)), fallible_function());
+    assert!(output.contains("\"enter parse_port\""));
+    assert!(!output.contains("__TracingAttrExit"));
+}
+
+#[test]
+fn test_timing() {
+    assert_snapshot!(apply_instrument(quote!(timing), fallible_function()));
+}
+
+#[test]
+fn test_events_and_timing_with_ret_and_err() {
+    assert_snapshot!(apply_instrument(
+        quote!(events(enter, exit), timing, ret, err),
+        fallible_function()
+    ));
+}
+
+#[test]
+fn test_events_and_timing_on_async_function() {
+    let item = quote! {
+        async fn fetch(id: u64) -> Option<String> {
+            lookup(id).await
+        }
+    };
+    assert_snapshot!(apply_instrument(quote!(events(enter, exit), timing), item));
+}
+
+#[test]
+fn test_duplicate_events_is_an_error() {
+    assert!(
+        instrument_error(quote!(events(enter), events(exit)), fallible_function())
+            .contains("expected only a single `events` argument")
+    );
+}
+
+#[test]
+fn test_empty_events_is_an_error() {
+    assert!(instrument_error(quote!(events()), fallible_function())
+        .contains("expected `events(enter)`, `events(exit)` or `events(enter, exit)`"));
+}
+
+#[test]
+fn test_unknown_event_is_an_error() {
+    assert!(instrument_error(quote!(events(close)), fallible_function())
+        .contains("invalid `events` argument"));
+}
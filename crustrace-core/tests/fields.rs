@@ -0,0 +1,86 @@
+use crustrace_core::instrument_impl;
+use insta::assert_snapshot;
+use proc_macro2::TokenStream;
+use quote::quote;
+use rust_format::{Formatter, RustFmt};
+
+fn apply_instrument(args: TokenStream, input: TokenStream) -> String {
+    let output = instrument_impl(args, input).expect("Should instrument successfully");
+    println!("Instrumented: {}", output);
+    let fmt_str = RustFmt::default()
+        .format_tokens(output)
+        .unwrap_or_else(|e| panic!("Format error: {}", e));
+    println!("Formatted: {}", fmt_str);
+    fmt_str
+}
+
+#[test]
+fn test_fields_with_expressions() {
+    let args = quote!(fields(user_id = req.user.id, %path, otel.kind = "server"));
+    let item = quote! {
+        fn handle(req: &Request, path: &str) -> Response {
+            respond(req, path)
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_fields_sigils() {
+    let args = quote!(fields(?state, %kind, count = ?items.len(), label = %kind.label()));
+    let item = quote! {
+        fn process(items: Vec<Item>, kind: Kind, state: State) {
+            state.apply(kind, items);
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_fields_empty() {
+    let args = quote!(fields(result, elapsed = tracing::field::Empty));
+    let item = quote! {
+        fn compute(x: u32) -> u32 {
+            x * 2
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_fields_override_parameter() {
+    let args = quote!(fields(id = %id, request.id = id));
+    let item = quote! {
+        fn lookup(id: Uuid, verbose: bool) -> Option<Record> {
+            find(id, verbose)
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_fields_with_skip() {
+    let args = quote!(skip(buf), fields(buf.len = buf.len()));
+    let item = quote! {
+        fn flush(buf: &mut Vec<u8>, force: bool) {
+            buf.clear();
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+#[should_panic(expected = "expected only a single `fields` argument")]
+fn test_duplicate_fields_error() {
+    let args = quote!(fields(a = 1), fields(b = 2));
+    let item = quote! {
+        fn test_function() {}
+    };
+
+    apply_instrument(args, item);
+}
@@ -0,0 +1,60 @@
+use crustrace_core::instrument_impl;
+use insta::assert_snapshot;
+use proc_macro2::TokenStream;
+use quote::quote;
+use rust_format::{Formatter, RustFmt};
+
+fn apply_instrument(args: TokenStream, input: TokenStream) -> String {
+    let output = instrument_impl(args, input).expect("Should instrument successfully");
+    println!("Instrumented: {}", output);
+    let fmt_str = RustFmt::default()
+        .format_tokens(output)
+        .unwrap_or_else(|e| panic!("Format error: {}", e));
+    println!("Formatted: {}", fmt_str);
+    fmt_str
+}
+
+#[test]
+fn test_follows_from_variable() {
+    let args = quote!(follows_from = causes);
+    let item = quote! {
+        fn test_function(causes: Vec<tracing::Id>) {}
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_follows_from_expression_with_other_args() {
+    let args = quote!(follows_from = [job.enqueued_by.clone()], parent = None, ret);
+    let item = quote! {
+        fn run_job(job: &Job) -> u32 {
+            job.id
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_follows_from_async_function() {
+    let args = quote!(follows_from = job.causes());
+    let item = quote! {
+        async fn run_job(job: Job) {
+            job.run().await
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_duplicate_follows_from_is_an_error() {
+    let args = quote!(follows_from = a, follows_from = b);
+    let item = quote! {
+        fn test_function() {}
+    };
+
+    let error = instrument_impl(args, item).expect_err("Duplicate follows_from should error");
+    assert!(error.to_string().contains("single `follows_from`"));
+}
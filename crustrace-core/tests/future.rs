@@ -0,0 +1,108 @@
+use crustrace_core::instrument_impl;
+use insta::assert_snapshot;
+use proc_macro2::TokenStream;
+use quote::quote;
+use rust_format::{Formatter, RustFmt};
+
+fn format(output: TokenStream) -> String {
+    let fmt_str = RustFmt::default()
+        .format_tokens(output)
+        .unwrap_or_else(|e| panic!("Format error: {}", e));
+    println!("Formatted: {}", fmt_str);
+    fmt_str
+}
+
+fn apply_instrument(args: TokenStream, input: TokenStream) -> String {
+    format(instrument_impl(args, input).expect("Should instrument successfully"))
+}
+
+fn instrument_error(args: TokenStream, input: TokenStream) -> String {
+    instrument_impl(args, input)
+        .expect_err("Should fail to instrument")
+        .to_string()
+}
+
+#[test]
+fn test_impl_future_is_instrumented() {
+    let item = quote! {
+        fn fetch(&self, key: u32) -> impl Future<Output = Option<String>> + '_ {
+            let key = self.normalise(key);
+            async move { self.lookup(key).await }
+        }
+    };
+    assert_snapshot!(apply_instrument(quote!(future), item));
+}
+
+#[test]
+fn test_boxed_future_is_instrumented() {
+    let item = quote! {
+        fn fetch(&self, key: u32) -> std::pin::Pin<Box<dyn std::future::Future<Output = Option<String>> + Send + '_>> {
+            let key = self.normalise(key);
+            Box::pin(self.lookup(key))
+        }
+    };
+    assert_snapshot!(apply_instrument(quote!(future), item));
+}
+
+#[test]
+fn test_future_with_ret_and_err() {
+    let item = quote! {
+        fn connect(addr: String) -> impl Future<Output = Result<u32, String>> {
+            if addr.is_empty() {
+                return std::future::ready(Err("no address".to_string()));
+            }
+            std::future::ready(Ok(addr.len() as u32))
+        }
+    };
+    assert_snapshot!(apply_instrument(quote!(future, ret, err), item));
+}
+
+#[test]
+fn test_future_bound_after_other_bounds() {
+    let item = quote! {
+        fn fetch(&self) -> impl Send + Future<Output = u32> {
+            std::future::ready(1)
+        }
+    };
+    assert!(apply_instrument(quote!(future), item).contains("let __tracing_attr_future = {"));
+}
+
+#[test]
+fn test_future_without_future_return_type_is_an_error() {
+    let item = quote! {
+        fn fetch(&self) -> Option<u32> {
+            None
+        }
+    };
+    assert!(instrument_error(quote!(future), item)
+        .contains("`future` requires the function to return `impl Future<Output = ...>`"));
+}
+
+#[test]
+fn test_future_without_return_type_is_an_error() {
+    let item = quote! {
+        fn fetch(&self) {}
+    };
+    assert!(instrument_error(quote!(future), item).contains("`future` requires the function"));
+}
+
+#[test]
+fn test_boxed_closure_is_not_a_future() {
+    let item = quote! {
+        fn make(&self) -> Pin<Box<dyn Fn() -> u32>> {
+            Box::pin(|| 1)
+        }
+    };
+    assert!(instrument_error(quote!(future), item).contains("`future` requires the function"));
+}
+
+#[test]
+fn test_future_on_async_fn_is_an_error() {
+    let item = quote! {
+        async fn fetch(&self) -> u32 {
+            1
+        }
+    };
+    assert!(instrument_error(quote!(future), item)
+        .contains("an `async fn` is instrumented as one already"));
+}
@@ -0,0 +1,85 @@
+use crustrace_core::instrument_impl;
+use insta::assert_snapshot;
+use proc_macro2::TokenStream;
+use quote::quote;
+use rust_format::{Formatter, RustFmt};
+
+fn apply_instrument(args: TokenStream, input: TokenStream) -> String {
+    let output = instrument_impl(args, input).expect("Should instrument successfully");
+    println!("Instrumented: {}", output);
+    let fmt_str = RustFmt::default()
+        .format_tokens(output)
+        .unwrap_or_else(|e| panic!("Format error: {}", e));
+    println!("Formatted: {}", fmt_str);
+    fmt_str
+}
+
+#[test]
+fn test_level_uppercase_string() {
+    let args = quote!(level = "DEBUG");
+    let item = quote! {
+        fn test_function() {}
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_level_integer() {
+    let args = quote!(level = 4);
+    let item = quote! {
+        fn test_function() {}
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_level_identifier() {
+    let args = quote!(level = trace);
+    let item = quote! {
+        fn test_function() {}
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_level_path() {
+    let args = quote!(
+        level = tracing::Level::WARN,
+        ret(level = Level::ERROR),
+        err(level = 2)
+    );
+    let item = quote! {
+        fn test_function() -> Result<i32, String> {
+            Ok(42)
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_unknown_level_is_an_error() {
+    let args = quote!(level = "warning");
+    let item = quote! {
+        fn test_function() {}
+    };
+
+    let error = instrument_impl(args, item).expect_err("Unknown level should not instrument");
+    assert!(error.to_string().contains("unknown tracing level"));
+}
+
+#[test]
+fn test_unknown_ret_level_is_an_error() {
+    let args = quote!(ret(level = 6));
+    let item = quote! {
+        fn test_function() -> i32 {
+            42
+        }
+    };
+
+    let error = instrument_impl(args, item).expect_err("Unknown level should not instrument");
+    assert!(error.to_string().contains("unknown tracing level"));
+}
@@ -5,7 +5,17 @@ use quote::quote;
 use rust_format::{Formatter, RustFmt};
 
 fn apply_trace_all(input: TokenStream) -> String {
-    let output = trace_all_impl(input);
+    let output = trace_all_impl(TokenStream::new(), input).expect("Should trace successfully");
+    println!("Traced::::: {}", output);
+    let fmt_str = RustFmt::default()
+        .format_tokens(output)
+        .unwrap_or_else(|e| panic!("Format error: {}", e));
+    println!("Formatted:: {}", fmt_str);
+    fmt_str
+}
+
+fn apply_trace_all_deep(input: TokenStream) -> String {
+    let output = trace_all_impl(quote!(deep), input).expect("Should trace successfully");
     println!("Traced::::: {}", output);
     let fmt_str = RustFmt::default()
         .format_tokens(output)
@@ -67,7 +77,6 @@ fn test_ignores_fn_in_comments() {
 }
 
 #[test]
-#[ignore]
 fn test_ignores_already_instrumented_functions() {
     let input = quote! {
         #[crustrace::instrument]
@@ -288,3 +297,39 @@ fn test_trait_with_trait_method_and_default_method_rev() {
 
     assert_snapshot!(apply_trace_all(input));
 }
+
+#[test]
+fn test_deep_ignores_closures_calls_and_fn_pointers() {
+    let input = quote! {
+        fn outer(values: Vec<u32>) -> u32 {
+            let double = |x: u32| x * 2;
+            let apply: fn(u32) -> u32 = double_it;
+            let boxed: Box<dyn Fn(u32) -> u32> = Box::new(move |x| x + 1);
+            let unsafe_ptr: unsafe extern "C" fn() = noop;
+            let total: u32 = values.iter().map(|v| apply(*v)).sum();
+            helper(total) + boxed(double(1))
+        }
+    };
+
+    assert_snapshot!(apply_trace_all_deep(input));
+}
+
+#[test]
+fn test_deep_ignores_items_in_nested_blocks() {
+    let input = quote! {
+        fn outer(flag: bool) -> u32 {
+            if flag {
+                fn in_if() -> u32 { 1 }
+                in_if()
+            } else {
+                let f = || {
+                    fn in_closure() -> u32 { 2 }
+                    in_closure()
+                };
+                f()
+            }
+        }
+    };
+
+    assert_snapshot!(apply_trace_all_deep(input));
+}
@@ -0,0 +1,475 @@
+use crustrace_core::trace_all_impl;
+use insta::assert_snapshot;
+use proc_macro2::TokenStream;
+use quote::quote;
+use rust_format::{Formatter, RustFmt};
+
+fn apply_trace_all(args: TokenStream, input: TokenStream) -> String {
+    let output = trace_all_impl(args, input).expect("Should trace successfully");
+    println!("Traced: {}", output);
+    let fmt_str = RustFmt::default()
+        .format_tokens(output)
+        .unwrap_or_else(|e| panic!("Format error: {}", e));
+    println!("Formatted: {}", fmt_str);
+    fmt_str
+}
+
+#[test]
+fn test_omni_level_ret_target() {
+    let args = quote!(level = "debug", ret, target = "db");
+    let input = quote! {
+        fn connect(url: &str) -> u32 {
+            1
+        }
+
+        impl Pool {
+            fn get(&self) -> u32 {
+                2
+            }
+        }
+    };
+
+    assert_snapshot!(apply_trace_all(args, input));
+}
+
+#[test]
+fn test_omni_skip_applies_where_present() {
+    let args = quote!(skip(conn));
+    let input = quote! {
+        fn query(conn: &Connection, sql: &str) {}
+
+        fn ping() {}
+    };
+
+    assert_snapshot!(apply_trace_all(args, input));
+}
+
+#[test]
+fn test_omni_duplicate_argument_is_an_error() {
+    let args = quote!(ret, ret(Display));
+    let input = quote! {
+        fn first() {}
+    };
+
+    let error = trace_all_impl(args, input).expect_err("Duplicate omni argument should error");
+    assert!(error
+        .to_string()
+        .contains("expected only a single `ret` argument"));
+}
+
+#[test]
+fn test_omni_unknown_argument_lists_instrument_arguments() {
+    let args = quote!(level = "debug", lvl = "debug");
+    let input = quote! {
+        fn first() {}
+    };
+
+    let error = trace_all_impl(args, input).expect_err("Unknown omni argument should error");
+    assert!(error
+        .to_string()
+        .contains("unknown omni argument `lvl`, expected one of level, name"));
+}
+
+#[test]
+fn test_omni_skip_marker_leaves_items_untouched() {
+    let input = quote! {
+        #[crustrace::skip]
+        fn hot(x: u32) -> u32 {
+            x
+        }
+
+        #[skip]
+        impl Widget {
+            fn inner(&self) {}
+        }
+
+        fn traced() {}
+    };
+
+    assert_snapshot!(apply_trace_all(TokenStream::new(), input));
+}
+
+#[test]
+fn test_omni_merges_function_instrument_args() {
+    let args = quote!(level = "debug", ret, skip(conn));
+    let input = quote! {
+        #[instrument(level = "info", skip(sql))]
+        fn query(conn: &Connection, sql: &str) -> u32 {
+            1
+        }
+
+        #[::crustrace::instrument]
+        #[inline]
+        fn ping() -> bool {
+            true
+        }
+    };
+
+    assert_snapshot!(apply_trace_all(args, input));
+}
+
+#[test]
+fn test_omni_function_instrument_error_is_reported() {
+    let input = quote! {
+        #[instrument(skip(missing))]
+        fn query(sql: &str) {}
+    };
+
+    let output = trace_all_impl(TokenStream::new(), input).expect("Should trace successfully");
+    let output = output.to_string();
+    assert!(output.contains("attempting to skip non-existent parameter"));
+    assert!(!output.contains("instrument"), "got: {output}");
+}
+
+/// Errors from the module's arguments are reported too, alongside the function as written
+#[test]
+fn test_omni_module_instrument_error_is_reported() {
+    let input = quote! {
+        fn open(#[record(Pretty)] path: PathBuf) -> bool {
+            path.exists()
+        }
+    };
+
+    let output = trace_all_impl(TokenStream::new(), input).expect("Should trace successfully");
+    assert_eq!(
+        output.to_string(),
+        "compile_error ! (\"invalid `record` attribute, expected \
+         `#[record(Value | Debug | Display)]`\") ; \
+         fn open (path : PathBuf) -> bool { path . exists () }"
+    );
+}
+
+#[test]
+fn test_omni_include_exclude_filters() {
+    let args = quote!(
+        include = ["handle_*", "Parser::*"],
+        exclude = ["*_unchecked", "fmt"]
+    );
+    let input = quote! {
+        fn handle_get() {}
+
+        fn handle_unchecked() {}
+
+        fn helper() {}
+
+        impl Parser {
+            fn next(&self) {}
+        }
+
+        impl core::fmt::Display for Parser {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                Ok(())
+            }
+        }
+
+        impl Lexer {
+            fn next(&self) {}
+        }
+    };
+
+    assert_snapshot!(apply_trace_all(args, input));
+}
+
+#[test]
+fn test_omni_malformed_filter_is_an_error() {
+    let args = quote!(include = "handle_*");
+    let input = quote! {
+        fn handle_get() {}
+    };
+
+    let error = trace_all_impl(args, input).expect_err("Malformed include should error");
+    assert!(error
+        .to_string()
+        .contains("invalid `include` argument, expected `include = [\\\"pattern\\\", ...]`"));
+}
+
+#[test]
+fn test_omni_visibility_levels() {
+    let args = quote!(level = "warn", pub = "info", private = "trace");
+    let input = quote! {
+        pub fn api() {}
+
+        pub(crate) fn internal() {}
+
+        fn helper() {}
+
+        impl Render for Widget {
+            fn render(&self, out: &mut String) {}
+        }
+    };
+
+    assert_snapshot!(apply_trace_all(args, input));
+}
+
+#[test]
+fn test_omni_only_pub() {
+    let args = quote!(only_pub);
+    let input = quote! {
+        pub fn api() {}
+
+        pub(super) fn internal() {}
+
+        fn helper() {}
+    };
+
+    assert_snapshot!(apply_trace_all(args, input));
+}
+
+#[test]
+fn test_omni_unknown_visibility_level_is_an_error() {
+    let args = quote!(private = "verbose");
+    let input = quote! {
+        fn helper() {}
+    };
+
+    let error = trace_all_impl(args, input).expect_err("Unknown level should error");
+    assert!(error.to_string().contains("unknown tracing level"));
+}
+
+#[test]
+fn test_omni_skips_std_trait_impls() {
+    let input = quote! {
+        impl fmt::Debug for Widget {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                Ok(())
+            }
+        }
+
+        impl Clone for Widget {
+            fn clone(&self) -> Self {
+                Widget
+            }
+        }
+
+        impl Drop for Widget {
+            fn drop(&mut self) {}
+        }
+
+        impl Iterator for Widget {
+            type Item = u32;
+
+            fn next(&mut self) -> Option<u32> {
+                None
+            }
+        }
+    };
+
+    assert_snapshot!(apply_trace_all(quote!(), input.clone()));
+    assert_snapshot!(
+        "omni_instrument_trait_impls",
+        apply_trace_all(quote!(instrument_trait_impls = ["Drop"]), input)
+    );
+}
+
+#[test]
+fn test_omni_span_names() {
+    let input = quote! {
+        fn helper() {}
+
+        impl Calculator {
+            fn new() -> Self {
+                Calculator
+            }
+        }
+
+        impl Render for Calculator {
+            fn render(&self) {}
+        }
+
+        trait Render {
+            fn render_twice(&self) {}
+        }
+    };
+
+    assert_snapshot!(
+        "omni_span_names_type",
+        apply_trace_all(quote!(span_names = "type"), input.clone())
+    );
+    assert_snapshot!(
+        "omni_span_names_qualified",
+        apply_trace_all(quote!(span_names = "qualified"), input)
+    );
+}
+
+#[test]
+fn test_omni_module_targets_without_target_is_an_error() {
+    let args = quote!(module_targets);
+    let input = quote! {
+        fn helper() {}
+    };
+
+    let error =
+        trace_all_impl(args, input).expect_err("module_targets without target should error");
+    assert_eq!(
+        error.to_string(),
+        "compile_error ! (\"`module_targets` requires a `target`, as the default \
+         `module_path!()` target already includes nested modules\") ;"
+    );
+}
+
+#[test]
+fn test_omni_unknown_span_naming_is_an_error() {
+    let args = quote!(span_names = "long");
+    let input = quote! {
+        fn helper() {}
+    };
+
+    let error = trace_all_impl(args, input).expect_err("Unknown span naming should error");
+    assert!(error.to_string().contains("unknown span naming `long`"));
+}
+
+#[test]
+fn test_omni_module_paths() {
+    let input = quote! {
+        mod a {
+            fn top() {}
+
+            mod b {
+                fn f() {}
+
+                impl Parser {
+                    fn next(&self) {}
+                }
+            }
+        }
+    };
+
+    assert_snapshot!(
+        "omni_module_names",
+        apply_trace_all(quote!(module_names), input.clone())
+    );
+    assert_snapshot!(
+        "omni_module_targets",
+        apply_trace_all(quote!(target = "app", module_targets), input)
+    );
+}
+
+#[test]
+fn test_omni_deep_instruments_items_in_function_bodies() {
+    let input = quote! {
+        fn outer(x: u32) -> u32 {
+            fn helper(y: u32) -> u32 {
+                y + 1
+            }
+
+            struct Local;
+
+            impl Local {
+                fn get(&self) -> u32 {
+                    2
+                }
+            }
+
+            mod nested {
+                pub fn inner() {}
+            }
+
+            nested::inner();
+            helper(x) + Local.get()
+        }
+    };
+
+    assert_snapshot!("omni_shallow", apply_trace_all(quote!(), input.clone()));
+    assert_snapshot!("omni_deep", apply_trace_all(quote!(deep), input));
+}
+
+/// `deep` only reaches into the bodies of functions that are themselves instrumented
+#[test]
+fn test_omni_deep_leaves_bodies_of_excluded_functions() {
+    let input = quote! {
+        pub fn outer_unchecked(x: u32) -> u32 {
+            pub fn helper(y: u32) -> u32 {
+                y + 1
+            }
+
+            helper(x)
+        }
+
+        pub const fn constant() -> u32 {
+            pub fn const_helper() {}
+
+            const_helper();
+            1
+        }
+
+        fn private_outer() {
+            pub fn private_helper() {}
+
+            private_helper()
+        }
+    };
+
+    let output = apply_trace_all(quote!(deep, exclude = ["*_unchecked"], only_pub), input);
+    assert!(!output.contains("span!"), "got: {output}");
+}
+
+#[test]
+fn test_omni_skips_tests() {
+    let input = quote! {
+        fn add(a: u32, b: u32) -> u32 {
+            a + b
+        }
+
+        #[cfg(not(test))]
+        fn release_only() {}
+
+        #[test]
+        fn test_add_at_top_level() {}
+
+        #[cfg(test)]
+        mod tests {
+            fn helper() {}
+
+            #[test]
+            fn test_add() {}
+        }
+
+        #[cfg(all(test, feature = "fixtures"))]
+        mod fixtures {
+            pub fn fixture() -> u32 {
+                1
+            }
+        }
+
+        #[tokio::test]
+        async fn test_async() {}
+
+        #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+        async fn test_multi_thread() {}
+
+        #[bench]
+        fn bench_add(b: &mut Bencher) {}
+    };
+
+    assert_snapshot!(apply_trace_all(quote!(), input.clone()));
+    assert_snapshot!(
+        "omni_include_tests",
+        apply_trace_all(quote!(include_tests), input)
+    );
+}
+
+#[test]
+fn test_omni_const_twin_is_an_error() {
+    let args = quote!(const_twin = twin);
+    let input = quote! {
+        const fn double(x: u32) -> u32 { x * 2 }
+    };
+
+    let error = trace_all_impl(args, input).expect_err("const_twin on omni should error");
+    assert!(error
+        .to_string()
+        .contains("`const_twin` can only be given to `#[instrument]`"));
+}
+
+#[test]
+fn test_omni_future_is_an_error() {
+    let args = quote!(future);
+    let input = quote! {
+        fn fetch() -> impl Future<Output = u32> { std::future::ready(1) }
+    };
+
+    let error = trace_all_impl(args, input).expect_err("future on omni should error");
+    assert!(error
+        .to_string()
+        .contains("`future` can only be given to `#[instrument]`"));
+}
@@ -5,7 +5,7 @@ use quote::quote;
 use rust_format::{Formatter, RustFmt};
 
 fn apply_trace_all(input: TokenStream) -> String {
-    let output = trace_all_impl(input);
+    let output = trace_all_impl(TokenStream::new(), input).expect("Should trace successfully");
     println!("Traced::::: {}", output);
     let fmt_str = RustFmt::default()
         .format_tokens(output)
@@ -0,0 +1,59 @@
+use crustrace_core::instrument_impl;
+use insta::assert_snapshot;
+use proc_macro2::TokenStream;
+use quote::quote;
+use rust_format::{Formatter, RustFmt};
+
+fn apply_instrument(args: TokenStream, input: TokenStream) -> String {
+    let output = instrument_impl(args, input).expect("Should instrument successfully");
+    println!("Instrumented: {}", output);
+    let fmt_str = RustFmt::default()
+        .format_tokens(output)
+        .unwrap_or_else(|e| panic!("Format error: {}", e));
+    println!("Formatted: {}", fmt_str);
+    fmt_str
+}
+
+#[test]
+fn test_record_by_parameter_type() {
+    let args = quote!();
+    let item = quote! {
+        fn test_function(id: u64, name: &str, path: PathBuf, tags: Vec<String>) {}
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_record_attribute_override() {
+    let args = quote!();
+    let item = quote! {
+        fn test_function(#[record(Display)] path: PathBuf, #[record(Debug)] id: u64) {}
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_invalid_record_attribute() {
+    let item = quote! {
+        fn test_function(#[record(Pretty)] path: PathBuf) {}
+    };
+
+    let err = instrument_impl(TokenStream::new(), item).expect_err("Should reject the attribute");
+    assert!(err.to_string().contains("invalid `record` attribute"));
+}
+
+/// The function is emitted alongside the error, without the `#[record]` attributes that only
+/// `#[instrument]` resolves
+#[test]
+fn test_error_keeps_function_without_record_attributes() {
+    let item = quote! {
+        fn test_function(#[record(Pretty)] path: PathBuf, #[record(Display)] id: u32) {}
+    };
+
+    let err = instrument_impl(TokenStream::new(), item).expect_err("Should reject the attribute");
+    assert!(err
+        .to_string()
+        .ends_with("fn test_function (path : PathBuf , id : u32) { }"));
+}
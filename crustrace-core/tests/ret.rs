@@ -278,3 +278,65 @@ fn test_ret_with_self_referential_return() {
 
     assert_snapshot!(apply_instrument(args, item));
 }
+
+// Recording the return value on the span
+#[test]
+fn test_ret_record() {
+    let args = quote!(ret(record));
+    let item = quote! {
+        fn test_function(x: i32) -> i32 {
+            if x < 0 {
+                return 0;
+            }
+            x * 2
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_ret_record_display_with_err() {
+    let args = quote!(ret(Display, record), err);
+    let item = quote! {
+        fn test_function(input: &str) -> Result<u32, std::num::ParseIntError> {
+            input.parse()
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_ret_record_async() {
+    let args = quote!(ret(record));
+    let item = quote! {
+        async fn test_function() -> String {
+            "done".to_string()
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+#[should_panic(expected = "so it takes no `level`")]
+fn test_ret_record_with_level_error() {
+    let args = quote!(ret(record, level = "debug"));
+    let item = quote! {
+        fn test_function() -> i32 { 42 }
+    };
+
+    apply_instrument(args, item);
+}
+
+#[test]
+#[should_panic(expected = "`record` only applies to `ret`")]
+fn test_err_record_error() {
+    let args = quote!(err(record));
+    let item = quote! {
+        fn test_function() -> Result<i32, String> { Ok(42) }
+    };
+
+    apply_instrument(args, item);
+}
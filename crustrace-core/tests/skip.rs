@@ -0,0 +1,101 @@
+use crustrace_core::instrument_impl;
+use insta::assert_snapshot;
+use proc_macro2::TokenStream;
+use quote::quote;
+use rust_format::{Formatter, RustFmt};
+
+fn apply_instrument(args: TokenStream, input: TokenStream) -> String {
+    let output = instrument_impl(args, input).expect("Should instrument successfully");
+    println!("Instrumented: {}", output);
+    let fmt_str = RustFmt::default()
+        .format_tokens(output)
+        .unwrap_or_else(|e| panic!("Format error: {}", e));
+    println!("Formatted: {}", fmt_str);
+    fmt_str
+}
+
+#[test]
+fn test_skip_parameters() {
+    let args = quote!(skip(conn, buf));
+    let item = quote! {
+        fn write_row(conn: &mut Connection, buf: &mut Vec<u8>, row_id: u64) -> usize {
+            conn.write(buf, row_id)
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_skip_self_and_parameter() {
+    let args = quote!(skip(self, password));
+    let item = quote! {
+        fn login(&self, user: &str, password: &str) -> bool {
+            self.check(user, password)
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_skip_all() {
+    let args = quote!(level = "debug", skip_all);
+    let item = quote! {
+        fn handle(db: &Database, request: Request) -> Response {
+            db.handle(request)
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_skip_tuple_pattern_identifier() {
+    let args = quote!(skip(secret));
+    let item = quote! {
+        fn unpack((name, secret): (String, Secret), attempt: u32) -> bool {
+            check(name, secret, attempt)
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_skip_tuple_struct_pattern_identifier() {
+    let args = quote!(skip(secret));
+    let item = quote! {
+        fn unpack(Credentials(name, secret): Credentials, attempt: u32) -> bool {
+            check(name, secret, attempt)
+        }
+    };
+
+    assert_snapshot!(apply_instrument(args, item));
+}
+
+#[test]
+fn test_skip_nonexistent_parameter_error() {
+    let args = quote!(skip(missing));
+    let item = quote! {
+        fn test_function(present: u32) -> u32 { present }
+    };
+
+    let err = instrument_impl(args, item).expect_err("Should reject unknown parameter");
+    assert_eq!(
+        err.to_string(),
+        "compile_error ! (\"attempting to skip non-existent parameter\") ; \
+         fn test_function (present : u32) -> u32 { present }"
+    );
+}
+
+#[test]
+#[should_panic(expected = "expected only a single `skip` argument")]
+fn test_duplicate_skip_error() {
+    let args = quote!(skip(a), skip(b));
+    let item = quote! {
+        fn test_function(a: u32, b: u32) {}
+    };
+
+    apply_instrument(args, item);
+}
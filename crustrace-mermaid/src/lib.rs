@@ -451,6 +451,29 @@ where
         }
     }
 
+    /// Called when values are recorded on a span after it was created.
+    ///
+    /// Fields declared as `tracing::field::Empty` (such as the `return_value` of
+    /// `#[instrument(ret(record))]`) are only filled in here, replacing any earlier value.
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(node) = span.extensions().get::<Arc<Mutex<CallNode>>>() {
+                let mut recorded = Vec::new();
+                values.record(&mut FieldVisitor {
+                    fields: &mut recorded,
+                });
+
+                let fields = &mut node.lock().unwrap().fields;
+                for (key, value) in recorded {
+                    match fields.iter_mut().find(|(existing, _)| *existing == key) {
+                        Some((_, existing)) => *existing = value,
+                        None => fields.push((key, value)),
+                    }
+                }
+            }
+        }
+    }
+
     /// Called when a span closes.
     ///
     /// If the span has a parent, we attach the node into its parent’s children.
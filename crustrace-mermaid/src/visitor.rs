@@ -20,7 +20,8 @@ fn escape_mermaid(input: &str) -> String {
 /// A [`Visit`] implementation that captures span fields as `(key, value)` pairs.
 ///
 /// This visitor is used in [`MermaidLayer::on_new_span`](crate::MermaidLayer)
-/// to record parameters from instrumented functions.
+/// to record parameters from instrumented functions, and in `on_record` for
+/// values recorded on their spans later (like `ret(record)` return values).
 ///
 /// Values are formatted with [`Debug`], but with a small tweak:
 /// - If the `Debug` output is a quoted string (e.g. `"foo"`),
@@ -34,7 +35,7 @@ pub(crate) struct FieldVisitor<'a> {
 impl<'a> Visit for FieldVisitor<'a> {
     /// Record a field using its [`Debug`] representation.
     ///
-    /// Called once per span field by the `tracing` framework.
+    /// Called once per span field (or recorded value) by the `tracing` framework.
     fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
         // Format value using Debug (so numbers, strings, etc. all work)
         let raw = format!("{:?}", value);
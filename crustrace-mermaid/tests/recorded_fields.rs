@@ -0,0 +1,30 @@
+#[cfg(test)]
+mod recorded_fields_tests {
+    use crustrace::instrument;
+    use crustrace_mermaid::*;
+    use tracing::subscriber::set_default;
+    use tracing_subscriber::prelude::*;
+
+    #[instrument(ret(record))]
+    fn inner(x: i32, y: i32) -> i32 {
+        x + y
+    }
+
+    #[instrument(ret(record))]
+    fn outer(a: i32, b: i32) -> i32 {
+        inner(a + 1, b / 10) * 2
+    }
+
+    #[test]
+    fn snapshot_return_values() {
+        let layer = MermaidLayer::new()
+            .with_mode(GroupingMode::UniquePerCall)
+            .without_auto_flush();
+        let subscriber = tracing_subscriber::registry().with(layer.clone());
+
+        let _guard = set_default(subscriber); // scoped subscriber
+        outer(10, 20);
+
+        insta::assert_snapshot!(layer.render());
+    }
+}
@@ -0,0 +1,297 @@
+mod common;
+
+use common::NoopWaker;
+use core::future::Future;
+use core::pin::{pin, Pin};
+use core::task::{Context, Poll, Waker};
+use crustrace::{instrument, omni};
+use std::sync::Arc;
+use tracing::Span;
+
+/// A future that is pending the first time it is polled
+#[derive(Default)]
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+}
+
+fn current_span_name() -> Option<&'static str> {
+    Span::current().metadata().map(|metadata| metadata.name())
+}
+
+#[instrument]
+async fn yielding(x: u32) -> Option<&'static str> {
+    YieldNow::default().await;
+    assert_eq!(x, 7);
+    current_span_name()
+}
+
+#[instrument(ret, err)]
+async fn fallible(input: &str) -> Result<u32, core::num::ParseIntError> {
+    YieldNow::default().await;
+    let value: u32 = input.parse()?;
+    Ok(value * 2)
+}
+
+struct Counter(u32);
+
+impl Counter {
+    #[instrument(ret)]
+    async fn bump(&mut self, by: u32) -> u32 {
+        YieldNow::default().await;
+        self.0 += by;
+        self.0
+    }
+}
+
+fn assert_send<T: Send>(value: T) -> T {
+    value
+}
+
+#[test]
+fn span_is_only_entered_while_polled() {
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry());
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+
+    let mut future = pin!(assert_send(yielding(7)));
+    assert!(future.as_mut().poll(&mut cx).is_pending());
+    // Suspended at the `.await`: the span must not leak into the caller
+    assert_eq!(current_span_name(), None);
+    assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(Some("yielding")));
+    assert_eq!(current_span_name(), None);
+}
+
+#[test]
+fn ret_and_err_work_on_async_output() {
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry());
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+
+    let mut ok = pin!(assert_send(fallible("21")));
+    assert!(ok.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(ok.as_mut().poll(&mut cx), Poll::Ready(Ok(42)));
+
+    let mut err = pin!(assert_send(fallible("twenty-one")));
+    assert!(err.as_mut().poll(&mut cx).is_pending());
+    assert!(matches!(err.as_mut().poll(&mut cx), Poll::Ready(Err(_))));
+
+    let mut counter = Counter(1);
+    let mut bump = pin!(assert_send(counter.bump(2)));
+    assert!(bump.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(bump.as_mut().poll(&mut cx), Poll::Ready(3));
+}
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+trait Store {
+    fn get<'life0, 'async_trait>(
+        &'life0 self,
+        key: u32,
+    ) -> BoxFuture<'async_trait, Option<&'static str>>
+    where
+        'life0: 'async_trait,
+        Self: 'async_trait;
+}
+
+struct Memory;
+
+// Methods as `#[async_trait]` desugars them, with `#[instrument]` expanded afterwards
+impl Store for Memory {
+    #[instrument]
+    fn get<'life0, 'async_trait>(
+        &'life0 self,
+        key: u32,
+    ) -> BoxFuture<'async_trait, Option<&'static str>>
+    where
+        'life0: 'async_trait,
+        Self: 'async_trait,
+    {
+        Box::pin(async move {
+            let __self = self;
+            let key = key;
+            let __ret: Option<&'static str> = {
+                YieldNow::default().await;
+                assert_eq!(key, 3);
+                current_span_name()
+            };
+            #[allow(unreachable_code)]
+            __ret
+        })
+    }
+}
+
+struct Disk;
+
+#[omni]
+impl Store for Disk {
+    fn get<'life0, 'async_trait>(
+        &'life0 self,
+        key: u32,
+    ) -> BoxFuture<'async_trait, Option<&'static str>>
+    where
+        'life0: 'async_trait,
+        Self: 'async_trait,
+    {
+        Box::pin(async move {
+            YieldNow::default().await;
+            assert_eq!(key, 4);
+            current_span_name()
+        })
+    }
+}
+
+#[test]
+fn boxed_async_span_is_only_entered_while_polled() {
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry());
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+
+    let stores: [(&dyn Store, u32); 2] = [(&Memory, 3), (&Disk, 4)];
+    for (store, key) in stores {
+        let mut future = store.get(key);
+        // The span isn't entered while the future is created, nor between polls
+        assert_eq!(current_span_name(), None);
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(current_span_name(), None);
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(Some("get")));
+    }
+}
+
+struct Cache {
+    prefix: &'static str,
+}
+
+impl Cache {
+    /// Synchronous set-up, then a future borrowing `self`
+    #[instrument(future, ret)]
+    fn fetch(&self, key: u32) -> impl Future<Output = (Option<&'static str>, u32)> + '_ {
+        let prologue_span = current_span_name();
+        assert_eq!(self.prefix, "cache");
+        async move {
+            YieldNow::default().await;
+            (prologue_span.and(current_span_name()), key * 2)
+        }
+    }
+
+    #[instrument(future)]
+    fn fetch_boxed(
+        &self,
+        key: u32,
+    ) -> Pin<Box<dyn Future<Output = (Option<&'static str>, u32)> + Send + '_>> {
+        let prologue_span = current_span_name();
+        Box::pin(async move {
+            YieldNow::default().await;
+            (
+                prologue_span.and(current_span_name()),
+                key + self.prefix.len() as u32,
+            )
+        })
+    }
+}
+
+#[test]
+fn returned_future_span_covers_prologue_and_polls() {
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry());
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+    let cache = Cache { prefix: "cache" };
+
+    let mut fetch = pin!(assert_send(cache.fetch(4)));
+    assert_eq!(current_span_name(), None);
+    assert!(fetch.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(current_span_name(), None);
+    assert_eq!(
+        fetch.as_mut().poll(&mut cx),
+        Poll::Ready((Some("fetch"), 8))
+    );
+
+    let mut fetch_boxed = assert_send(cache.fetch_boxed(4));
+    assert_eq!(current_span_name(), None);
+    assert!(fetch_boxed.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(current_span_name(), None);
+    assert_eq!(
+        fetch_boxed.as_mut().poll(&mut cx),
+        Poll::Ready((Some("fetch_boxed"), 9))
+    );
+}
+
+#[async_trait::async_trait]
+trait Remote {
+    async fn get(&self, key: u32) -> Option<&'static str>;
+
+    #[instrument]
+    async fn get_default(&self, key: u32) -> Option<&'static str> {
+        YieldNow::default().await;
+        assert_eq!(key, 1);
+        current_span_name()
+    }
+}
+
+struct Method;
+
+#[async_trait::async_trait]
+impl Remote for Method {
+    #[instrument]
+    async fn get(&self, key: u32) -> Option<&'static str> {
+        YieldNow::default().await;
+        assert_eq!(key, 2);
+        current_span_name()
+    }
+}
+
+struct OmniAbove;
+
+// `#[omni]` sees the `async fn` before `#[async_trait]` desugars it
+#[omni]
+#[async_trait::async_trait]
+impl Remote for OmniAbove {
+    async fn get(&self, key: u32) -> Option<&'static str> {
+        YieldNow::default().await;
+        assert_eq!(key, 3);
+        current_span_name()
+    }
+}
+
+struct OmniBelow;
+
+// `#[omni]` sees the method `#[async_trait]` desugared into a boxed future
+#[async_trait::async_trait]
+#[omni]
+impl Remote for OmniBelow {
+    async fn get(&self, key: u32) -> Option<&'static str> {
+        YieldNow::default().await;
+        assert_eq!(key, 4);
+        current_span_name()
+    }
+}
+
+#[test]
+fn async_trait_span_is_entered_inside_the_boxed_future() {
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry());
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+
+    let futures = [
+        (Method.get_default(1), "get_default"),
+        (Method.get(2), "get"),
+        (OmniAbove.get(3), "get"),
+        (OmniBelow.get(4), "get"),
+    ];
+    for (mut future, name) in futures {
+        assert_eq!(current_span_name(), None);
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(current_span_name(), None);
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(Some(name)));
+    }
+}
@@ -0,0 +1,176 @@
+//! A tracing layer capturing what instrumented code reports, and a minimal executor, shared by
+//! the runtime tests.
+
+// Each test crate only uses some of these
+#![allow(dead_code)]
+
+use core::fmt;
+use core::future::Future;
+use core::task::{Context, Poll, Waker};
+use std::sync::{Arc, Mutex};
+use std::task::Wake;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{self, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+/// The `(name, level, target)` of a span
+pub type SpanInfo = (&'static str, Level, &'static str);
+
+/// What a [`Capture`] layer saw, in the order it happened
+#[derive(Clone, Debug, Default)]
+pub struct Captured {
+    /// Every new span
+    pub spans: Vec<SpanInfo>,
+    /// `name=kind:value` for each field of a new span, with the kind it was recorded as (`u64`,
+    /// `str` or `debug`)
+    pub span_fields: Vec<String>,
+    /// `"<span>: <message>"` for every event
+    pub messages: Vec<String>,
+    /// `(field, value)` for each of the watched fields in events and span records
+    pub values: Vec<(String, String)>,
+    /// Names of the spans that recorded `elapsed_us`
+    pub timed: Vec<String>,
+    /// `(span, cause)` names for every follows-from link
+    pub links: Vec<(&'static str, &'static str)>,
+}
+
+/// A layer capturing spans, events and span records, watching the values of the fields given to
+/// [`Capture::watching`]
+#[derive(Clone, Default)]
+pub struct Capture {
+    watched: &'static [&'static str],
+    captured: Arc<Mutex<Captured>>,
+}
+
+impl Capture {
+    /// A layer also capturing the values of `fields` wherever they are recorded
+    pub fn watching(fields: &'static [&'static str]) -> Self {
+        Self {
+            watched: fields,
+            ..Self::default()
+        }
+    }
+
+    /// Run `f` with this layer's subscriber installed, returning what it captured
+    pub fn run(self, f: impl FnOnce()) -> Captured {
+        let subscriber = tracing_subscriber::registry().with(self.clone());
+        tracing::subscriber::with_default(subscriber, f);
+        let captured = self.captured.lock().unwrap().clone();
+        captured
+    }
+
+    fn watch(&self, fields: &FieldValues) {
+        let values = fields
+            .0
+            .iter()
+            .filter(|(name, _, _)| self.watched.contains(name))
+            .map(|(name, _, value)| (name.to_string(), value.clone()));
+        self.captured.lock().unwrap().values.extend(values);
+    }
+}
+
+/// Run `f` with a subscriber installed, returning what it captured
+pub fn capture(f: impl FnOnce()) -> Captured {
+    Capture::default().run(f)
+}
+
+/// The `(name, kind, value)` of each field visited
+#[derive(Default)]
+struct FieldValues(Vec<(&'static str, &'static str, String)>);
+
+impl FieldValues {
+    fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(field, _, _)| *field == name)
+            .map(|(_, _, value)| value.as_str())
+    }
+}
+
+impl Visit for FieldValues {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.push((field.name(), "u64", value.to_string()));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name(), "str", value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.push((field.name(), "debug", format!("{value:?}")));
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Capture {
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: layer::Context<'_, S>) {
+        let metadata = attrs.metadata();
+        let mut fields = FieldValues::default();
+        attrs.record(&mut fields);
+        let mut captured = self.captured.lock().unwrap();
+        captured
+            .spans
+            .push((metadata.name(), *metadata.level(), metadata.target()));
+        captured.span_fields.extend(
+            fields
+                .0
+                .iter()
+                .map(|(name, kind, value)| format!("{name}={kind}:{value}")),
+        );
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: layer::Context<'_, S>) {
+        let mut fields = FieldValues::default();
+        event.record(&mut fields);
+        let parent = ctx
+            .event_span(event)
+            .map(|span| span.name())
+            .unwrap_or_default();
+        let message = fields.get("message").unwrap_or_default();
+        self.captured
+            .lock()
+            .unwrap()
+            .messages
+            .push(format!("{parent}: {message}"));
+        self.watch(&fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: layer::Context<'_, S>) {
+        let mut fields = FieldValues::default();
+        values.record(&mut fields);
+        if fields.get("elapsed_us").is_some() {
+            let name = ctx.span(id).map(|span| span.name()).unwrap_or_default();
+            self.captured.lock().unwrap().timed.push(name.to_string());
+        }
+        self.watch(&fields);
+    }
+
+    fn on_follows_from(&self, span: &Id, follows: &Id, ctx: layer::Context<'_, S>) {
+        let name = |id| ctx.span(id).map_or("?", |span| span.name());
+        self.captured
+            .lock()
+            .unwrap()
+            .links
+            .push((name(span), name(follows)));
+    }
+}
+
+/// A waker that does nothing, for polling futures by hand
+pub struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Poll `future` until it completes
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = core::pin::pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
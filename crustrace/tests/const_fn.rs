@@ -0,0 +1,35 @@
+//! `const fn`s are left alone by `#[omni]`, and `#[instrument(const_twin = ...)]` keeps a const
+//! copy alongside the instrumented function.
+
+mod common;
+
+use crustrace::{instrument, omni};
+
+#[instrument(const_twin = double_const)]
+pub const fn double(x: u32) -> u32 {
+    x * 2
+}
+
+const DOUBLED: u32 = double_const(21);
+
+#[omni]
+mod math {
+    pub const fn square(x: u32) -> u32 {
+        x * x
+    }
+
+    pub const SQUARED: u32 = square(3);
+
+    pub fn cube(x: u32) -> u32 {
+        square(x) * x
+    }
+}
+
+#[test]
+fn const_fns_stay_const() {
+    let captured = common::capture(|| assert_eq!(double(DOUBLED) + math::cube(2), 92));
+    assert_eq!(math::SQUARED, 9);
+
+    let names: Vec<_> = captured.spans.iter().map(|span| span.0).collect();
+    assert_eq!(names, ["double", "cube"]);
+}
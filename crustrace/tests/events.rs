@@ -0,0 +1,114 @@
+//! Runtime coverage for `events(enter, exit)` and `timing`, however the function returns.
+
+mod common;
+
+use common::block_on;
+use crustrace::{instrument, omni};
+
+/// Run `f`, returning the event messages and the spans that recorded their timing
+fn capture(f: impl FnOnce()) -> (Vec<String>, Vec<String>) {
+    let captured = common::capture(f);
+    (captured.messages, captured.timed)
+}
+
+#[instrument(events(enter, exit), timing)]
+fn parse_port(input: &str) -> Result<u16, core::num::ParseIntError> {
+    let port: u16 = input.parse()?;
+    tracing::info!("parsed");
+    Ok(port)
+}
+
+#[instrument(events(exit), timing)]
+fn explode(message: &str) {
+    panic!("{}", message);
+}
+
+#[instrument(events(enter, exit), timing, ret)]
+async fn doubled(x: u32) -> u32 {
+    core::future::ready(x).await * 2
+}
+
+#[omni(events(enter, exit), timing, skip_all)]
+mod jobs {
+    pub fn run(steps: u32) -> u32 {
+        (0..steps).map(step).sum()
+    }
+
+    fn step(n: u32) -> u32 {
+        n * 10
+    }
+}
+
+#[test]
+fn enter_and_exit_events_around_the_body() {
+    let (messages, timed) = capture(|| assert_eq!(parse_port("80"), Ok(80)));
+    assert_eq!(
+        messages,
+        [
+            "parse_port: enter parse_port",
+            "parse_port: parsed",
+            "parse_port: exit parse_port"
+        ]
+    );
+    assert_eq!(timed, ["parse_port"]);
+}
+
+#[test]
+fn exit_event_on_question_mark_return() {
+    let (messages, timed) = capture(|| assert!(parse_port("eighty").is_err()));
+    assert_eq!(
+        messages,
+        [
+            "parse_port: enter parse_port",
+            "parse_port: exit parse_port"
+        ]
+    );
+    assert_eq!(timed, ["parse_port"]);
+}
+
+#[test]
+fn exit_event_on_panic() {
+    let (messages, timed) = capture(|| {
+        let result = std::panic::catch_unwind(|| explode("boom"));
+        assert!(result.is_err());
+    });
+    assert_eq!(messages, ["explode: exit explode"]);
+    assert_eq!(timed, ["explode"]);
+}
+
+// An `async fn` body, including its span and enter event, only runs once it is polled
+#[test]
+fn exit_event_when_async_function_completes() {
+    let (messages, timed) = capture(|| {
+        let future = doubled(4);
+        tracing::info!("created");
+        assert_eq!(block_on(future), 8);
+    });
+    assert_eq!(
+        messages,
+        [
+            ": created",
+            "doubled: enter doubled",
+            "doubled: ",
+            "doubled: exit doubled"
+        ]
+    );
+    assert_eq!(timed, ["doubled"]);
+}
+
+#[test]
+fn omni_applies_events_and_timing_to_every_function() {
+    let (messages, timed) = capture(|| assert_eq!(jobs::run(2), 10));
+    assert_eq!(
+        messages,
+        [
+            "run: enter run",
+            "step: enter step",
+            "step: exit step",
+            "step: enter step",
+            "step: exit step",
+            "run: exit run"
+        ]
+    );
+    assert_eq!(timed, ["step", "step", "run"]);
+}
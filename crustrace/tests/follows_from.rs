@@ -0,0 +1,26 @@
+mod common;
+
+use crustrace::instrument;
+use tracing::span::Id;
+use tracing::Span;
+
+#[instrument(follows_from = causes, skip(causes))]
+fn run_job(causes: Vec<Option<Id>>) -> Option<&'static str> {
+    Span::current().metadata().map(|metadata| metadata.name())
+}
+
+#[test]
+fn span_follows_from_each_cause() {
+    let captured = common::capture(|| {
+        let enqueue = tracing::info_span!("enqueue");
+        let schedule = tracing::info_span!("schedule");
+
+        let current = run_job(vec![enqueue.id(), None, schedule.id()]);
+        assert_eq!(current, Some("run_job"));
+    });
+
+    assert_eq!(
+        captured.links,
+        [("run_job", "enqueue"), ("run_job", "schedule")]
+    );
+}
@@ -0,0 +1,347 @@
+mod common;
+
+use common::SpanInfo;
+use crustrace::omni;
+use tracing::Level;
+
+/// Run `f`, returning its result and every span created while it ran
+fn capture_spans<R>(f: impl FnOnce() -> R) -> (R, Vec<SpanInfo>) {
+    let mut result = None;
+    let captured = common::capture(|| result = Some(f()));
+    (result.unwrap(), captured.spans)
+}
+
+#[omni(level = "debug", target = "db", skip(password))]
+mod db {
+    pub fn connect(url: &str, password: &str) -> u32 {
+        assert!(!password.is_empty());
+        ping(url.len() as u32)
+    }
+
+    fn ping(attempt: u32) -> u32 {
+        attempt
+    }
+}
+
+#[test]
+fn omni_arguments_apply_to_every_function() {
+    let (result, spans) = capture_spans(|| db::connect("db://", "hunter2"));
+    assert_eq!(result, 5);
+
+    assert_eq!(
+        spans,
+        [
+            ("connect", Level::DEBUG, "db"),
+            ("ping", Level::DEBUG, "db")
+        ]
+    );
+}
+
+#[omni(level = "debug")]
+mod overridden {
+    #[crustrace::instrument(level = "info", target = "api")]
+    pub fn handle(x: u32) -> u32 {
+        hot(x) + skipped::cold(x)
+    }
+
+    #[crustrace::skip]
+    fn hot(x: u32) -> u32 {
+        x
+    }
+
+    #[crustrace::skip]
+    pub mod skipped {
+        pub fn cold(x: u32) -> u32 {
+            x
+        }
+    }
+}
+
+#[test]
+fn omni_respects_skip_and_function_overrides() {
+    let (result, spans) = capture_spans(|| overridden::handle(2));
+    assert_eq!(result, 4);
+
+    // A single span for `handle`, with its own level and target
+    assert_eq!(spans, [("handle", Level::INFO, "api")]);
+}
+
+#[omni(include = ["run_*", "Worker::*"], exclude = ["*_quietly"])]
+mod filtered {
+    pub struct Worker;
+
+    impl Worker {
+        pub fn step(&self) -> u32 {
+            run_quietly()
+        }
+    }
+
+    pub fn run_all() -> u32 {
+        Worker.step() + helper()
+    }
+
+    fn run_quietly() -> u32 {
+        1
+    }
+
+    fn helper() -> u32 {
+        1
+    }
+}
+
+#[test]
+fn omni_filters_functions_by_name() {
+    let (result, spans) = capture_spans(filtered::run_all);
+    assert_eq!(result, 2);
+
+    let names: Vec<_> = spans.iter().map(|span| span.0).collect();
+    assert_eq!(names, ["run_all", "step"]);
+}
+
+#[omni(pub = "info", restricted = "debug", private = "trace")]
+mod by_visibility {
+    pub fn api() -> u32 {
+        internal() + helper()
+    }
+
+    pub(crate) fn internal() -> u32 {
+        1
+    }
+
+    fn helper() -> u32 {
+        1
+    }
+}
+
+#[omni(only_pub)]
+mod only_pub {
+    pub fn api() -> u32 {
+        helper()
+    }
+
+    fn helper() -> u32 {
+        1
+    }
+}
+
+#[test]
+fn omni_levels_follow_visibility() {
+    let (result, spans) = capture_spans(|| by_visibility::api() + only_pub::api());
+    assert_eq!(result, 3);
+
+    let spans: Vec<_> = spans.iter().map(|span| (span.0, span.1)).collect();
+    assert_eq!(
+        spans,
+        [
+            ("api", Level::INFO),
+            ("internal", Level::DEBUG),
+            ("helper", Level::TRACE),
+            ("api", Level::INFO),
+        ]
+    );
+}
+
+#[omni]
+mod shapes {
+    use core::fmt;
+
+    pub struct Square(pub u32);
+
+    impl fmt::Debug for Square {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "Square({})", self.0)
+        }
+    }
+
+    impl Clone for Square {
+        fn clone(&self) -> Self {
+            Square(self.0)
+        }
+    }
+
+    pub fn area(square: Square) -> u32 {
+        let copy = square.clone();
+        copy.0 * square.0
+    }
+}
+
+#[test]
+fn omni_skips_std_trait_impls() {
+    let (result, spans) = capture_spans(|| shapes::area(shapes::Square(3)));
+    assert_eq!(result, 9);
+
+    // Recording `square` with `Debug` doesn't create a span for `fmt`, nor does cloning it
+    let names: Vec<_> = spans.iter().map(|span| span.0).collect();
+    assert_eq!(names, ["area"]);
+}
+
+#[omni(span_names = "qualified")]
+mod calculator {
+    pub trait Reset {
+        fn reset(&mut self);
+    }
+
+    pub struct Calculator(pub u32);
+
+    impl Calculator {
+        pub fn new() -> Self {
+            Calculator(0)
+        }
+
+        pub fn add(&mut self, x: u32) -> u32 {
+            self.0 += x;
+            self.0
+        }
+    }
+
+    impl Reset for Calculator {
+        fn reset(&mut self) {
+            self.0 = 0;
+        }
+    }
+}
+
+#[test]
+fn omni_span_names_are_qualified() {
+    use calculator::Reset;
+
+    let (result, spans) = capture_spans(|| {
+        let mut calculator = calculator::Calculator::new();
+        let sum = calculator.add(2);
+        calculator.reset();
+        sum
+    });
+    assert_eq!(result, 2);
+
+    let names: Vec<_> = spans.iter().map(|span| span.0).collect();
+    assert_eq!(
+        names,
+        [
+            "Calculator::new",
+            "Calculator::add",
+            "<Calculator as Reset>::reset"
+        ]
+    );
+}
+
+#[omni(module_names)]
+mod outer {
+    pub fn top() -> u32 {
+        inner::leaf()
+    }
+
+    pub mod inner {
+        pub fn leaf() -> u32 {
+            1
+        }
+    }
+}
+
+#[omni(target = "app", module_targets)]
+mod targeted {
+    pub fn top() -> u32 {
+        inner::leaf()
+    }
+
+    pub mod inner {
+        pub fn leaf() -> u32 {
+            1
+        }
+    }
+}
+
+#[test]
+fn omni_nested_modules_name_and_target_spans() {
+    let (result, spans) = capture_spans(|| outer::top() + targeted::top());
+    assert_eq!(result, 2);
+
+    let spans: Vec<_> = spans.iter().map(|span| (span.0, span.2)).collect();
+    assert_eq!(
+        spans,
+        [
+            ("top", "omni_args::outer"),
+            ("inner::leaf", "omni_args::outer::inner"),
+            ("top", "app"),
+            ("leaf", "app::inner"),
+        ]
+    );
+}
+
+#[omni(deep)]
+mod deep {
+    pub fn outer(x: u32) -> u32 {
+        fn helper(y: u32) -> u32 {
+            y * 2
+        }
+
+        let add_one = |y: u32| y + 1;
+        add_one(helper(x))
+    }
+}
+
+#[test]
+fn omni_deep_instruments_nested_functions() {
+    let (result, spans) = capture_spans(|| deep::outer(2));
+    assert_eq!(result, 5);
+
+    let names: Vec<_> = spans.iter().map(|span| span.0).collect();
+    assert_eq!(names, ["outer", "helper"]);
+}
+
+// `err` only applies to the functions returning a `Result`
+#[omni(err)]
+mod parsing {
+    pub fn parse(input: &str) -> Result<u32, std::num::ParseIntError> {
+        input.parse()
+    }
+
+    pub fn io_parse(input: &str) -> std::io::Result<u32> {
+        parse(input).map_err(std::io::Error::other)
+    }
+
+    pub fn plain() -> u32 {
+        1
+    }
+
+    pub fn unit() {}
+}
+
+#[test]
+fn omni_err_skips_functions_not_returning_a_result() {
+    let spans = common::capture(|| {
+        assert_eq!(parsing::parse("3"), Ok(3));
+        assert!(parsing::io_parse("x").is_err());
+        assert_eq!(parsing::plain(), 1);
+        parsing::unit();
+    })
+    .spans;
+
+    let names: Vec<_> = spans.iter().map(|span| span.0).collect();
+    assert_eq!(names, ["parse", "io_parse", "parse", "plain", "unit"]);
+}
+
+// `ret` records return values that are neither `Value` nor `Debug` by type name
+#[omni(ret)]
+mod handles {
+    pub struct Handle(pub u32);
+
+    pub fn open() -> Handle {
+        Handle(1)
+    }
+
+    pub fn count() -> u32 {
+        2
+    }
+}
+
+#[test]
+fn omni_ret_compiles_for_any_return_type() {
+    let spans = common::capture(|| {
+        assert_eq!(handles::open().0, 1);
+        assert_eq!(handles::count(), 2);
+    })
+    .spans;
+
+    let names: Vec<_> = spans.iter().map(|span| span.0).collect();
+    assert_eq!(names, ["open", "count"]);
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use crustrace::instrument;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Displayed as its name, but only debuggable as the whole struct
+struct Job {
+    name: &'static str,
+}
+
+impl fmt::Display for Job {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+/// Neither `tracing::Value` nor `Debug`
+struct Handle;
+
+#[instrument]
+fn by_type(id: u64, name: &str, path: PathBuf) {}
+
+#[instrument]
+fn by_fallback(limit: Option<u64>, handle: &Handle, path: &PathBuf) {}
+
+#[instrument]
+fn generic<T>(item: T) {}
+
+#[crustrace::omni]
+mod opaque {
+    pub fn close(_handle: super::Handle) {}
+}
+
+#[instrument]
+fn with_override(#[record(Display)] job: Job, #[record(Debug)] id: u64) {}
+
+// Every way `#[omni]` can leave a function uninstrumented, which must still remove the
+// `#[record]` attributes that only `#[instrument]` understands
+#[crustrace::omni(exclude = ["excluded"])]
+mod passed_over {
+    use super::Job;
+
+    pub fn excluded(#[record(Display)] job: Job) -> &'static str {
+        job.name
+    }
+
+    pub const fn constant(#[record(Debug)] x: u32) -> u32 {
+        x
+    }
+
+    #[crustrace::skip]
+    pub fn skipped(#[record(Debug)] x: u32) -> u32 {
+        x
+    }
+
+    #[crustrace::skip]
+    pub mod skipped_module {
+        pub fn inner(#[record(Debug)] x: u32) -> u32 {
+            x
+        }
+    }
+
+    pub struct Id(pub u32);
+
+    impl PartialEq for Id {
+        fn eq(&self, #[record(Debug)] other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    #[cfg(test)]
+    pub mod test_helpers {
+        pub fn helper(#[record(Debug)] x: u32) -> u32 {
+            x
+        }
+    }
+}
+
+#[crustrace::omni(only_pub)]
+mod private_passed_over {
+    fn private(#[record(Debug)] x: u32) -> u32 {
+        x
+    }
+
+    pub fn public(x: u32) -> u32 {
+        private(x)
+    }
+}
+
+/// Run `f`, returning `name=kind:value` for every field recorded on a new span
+fn recorded(f: impl FnOnce()) -> Vec<String> {
+    common::capture(f).span_fields
+}
+
+#[test]
+fn records_by_parameter_type() {
+    let fields = recorded(|| by_type(7, "seven", PathBuf::from("/tmp")));
+    assert_eq!(
+        fields,
+        ["id=u64:7", "name=str:seven", "path=debug:\"/tmp\""]
+    );
+}
+
+#[test]
+fn records_any_type_by_fallback() {
+    let fields = recorded(|| by_fallback(Some(3), &Handle, &PathBuf::from("/tmp")));
+    assert_eq!(
+        fields,
+        [
+            "limit=u64:3",
+            "handle=str:&record::Handle",
+            "path=debug:\"/tmp\""
+        ]
+    );
+
+    let fields = recorded(|| generic(Handle));
+    assert_eq!(fields, ["item=str:record::Handle"]);
+
+    let fields = recorded(|| opaque::close(Handle));
+    assert_eq!(fields, ["_handle=str:record::Handle"]);
+}
+
+#[test]
+fn record_attribute_overrides_type() {
+    let fields = recorded(|| with_override(Job { name: "build" }, 7));
+    assert_eq!(fields, ["job=debug:build", "id=debug:7"]);
+}
+
+#[test]
+fn passed_over_functions_drop_record_attributes() {
+    let fields = recorded(|| {
+        assert_eq!(passed_over::excluded(Job { name: "build" }), "build");
+        assert_eq!(passed_over::constant(1), 1);
+        assert_eq!(passed_over::skipped(2), 2);
+        assert_eq!(passed_over::skipped_module::inner(3), 3);
+        assert_eq!(passed_over::test_helpers::helper(4), 4);
+        assert!(passed_over::Id(5) == passed_over::Id(5));
+    });
+    assert!(fields.is_empty());
+
+    let fields = recorded(|| assert_eq!(private_passed_over::public(6), 6));
+    assert_eq!(fields, ["x=u64:6"]);
+}
@@ -0,0 +1,167 @@
+//! Compile-pass coverage for `ret` on every kind of function the attribute accepts.
+
+mod common;
+
+use common::{block_on, Capture};
+use crustrace::instrument;
+
+/// Run `f`, returning the `return_value` and `error` fields of every event and span record
+fn capture(f: impl FnOnce()) -> Vec<(String, String)> {
+    Capture::watching(&["return_value", "error"]).run(f).values
+}
+
+fn ret(value: &str) -> (String, String) {
+    ("return_value".to_string(), value.to_string())
+}
+
+#[instrument(ret)]
+fn early_return(x: i32) -> i32 {
+    if x < 0 {
+        return 0;
+    }
+    x * 2
+}
+
+#[instrument(ret, err)]
+fn question_mark(input: &str) -> Result<u8, core::num::ParseIntError> {
+    let value: u8 = input.parse()?;
+    Ok(value + 1)
+}
+
+#[instrument(ret)]
+fn first_mut(values: &mut [u8]) -> &mut u8 {
+    &mut values[0]
+}
+
+#[instrument(ret)]
+fn evens(values: &[u8]) -> impl Iterator<Item = &u8> + '_ {
+    values.iter().filter(|v| *v % 2 == 0)
+}
+
+#[instrument(ret, skip(ptr))]
+unsafe fn read_raw(ptr: *const u32) -> u32 {
+    *ptr
+}
+
+#[instrument(ret)]
+extern "C" fn c_abi(x: u32) -> u32 {
+    x + 1
+}
+
+#[instrument(ret)]
+async fn awaits(x: u32) -> u32 {
+    core::future::ready(x).await * 3
+}
+
+#[derive(Debug)]
+struct Wrapper(Vec<u8>);
+
+impl Wrapper {
+    #[instrument(ret)]
+    fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+
+    #[instrument(ret)]
+    fn last_mut(&mut self, fallback: bool) -> Option<&mut u8> {
+        if fallback {
+            return self.0.first_mut();
+        }
+        self.0.last_mut()
+    }
+}
+
+#[test]
+fn ret_captures_early_return() {
+    assert_eq!(capture(|| assert_eq!(early_return(-5), 0)), vec![ret("0")]);
+    assert_eq!(capture(|| assert_eq!(early_return(4), 8)), vec![ret("8")]);
+}
+
+#[test]
+fn ret_and_err_capture_question_mark() {
+    assert_eq!(
+        capture(|| assert_eq!(question_mark("41"), Ok(42))),
+        vec![ret("42")]
+    );
+    let captured = capture(|| assert!(question_mark("x").is_err()));
+    assert_eq!(captured.len(), 1);
+    assert_eq!(captured[0].0, "error");
+}
+
+#[test]
+fn ret_returns_borrows_of_parameters() {
+    let mut values = [7, 8, 9];
+    assert_eq!(capture(|| *first_mut(&mut values) = 1), vec![ret("7")]);
+    assert_eq!(values, [1, 8, 9]);
+
+    let evens_seen = capture(|| assert_eq!(evens(&values).count(), 1));
+    assert_eq!(evens_seen.len(), 1);
+}
+
+#[test]
+fn ret_on_unsafe_and_extern_functions() {
+    let value = 5;
+    assert_eq!(
+        capture(|| assert_eq!(unsafe { read_raw(&value) }, 5)),
+        vec![ret("5")]
+    );
+    assert_eq!(capture(|| assert_eq!(c_abi(1), 2)), vec![ret("2")]);
+}
+
+#[test]
+fn ret_on_async_function() {
+    assert_eq!(
+        capture(|| assert_eq!(block_on(awaits(2)), 6)),
+        vec![ret("6")]
+    );
+}
+
+#[test]
+fn ret_on_methods() {
+    assert_eq!(
+        capture(|| assert_eq!(Wrapper(vec![1, 2]).into_inner(), vec![1, 2])),
+        vec![ret("[1, 2]")]
+    );
+
+    let mut wrapper = Wrapper(vec![1, 2]);
+    assert_eq!(
+        capture(|| *wrapper.last_mut(true).unwrap() = 0),
+        vec![ret("Some(1)")]
+    );
+    assert_eq!(wrapper.0, vec![0, 2]);
+}
+
+#[instrument(ret(record))]
+fn recorded(x: i32) -> i32 {
+    if x < 0 {
+        return 0;
+    }
+    x * 2
+}
+
+#[instrument(ret(Display, record), err)]
+fn recorded_result(input: &str) -> Result<u8, core::num::ParseIntError> {
+    input.parse()
+}
+
+#[instrument(ret(record))]
+async fn recorded_async(x: u32) -> u32 {
+    core::future::ready(x).await + 1
+}
+
+#[test]
+fn ret_record_records_on_the_span() {
+    assert_eq!(capture(|| assert_eq!(recorded(-1), 0)), vec![ret("0")]);
+    assert_eq!(capture(|| assert_eq!(recorded(3), 6)), vec![ret("6")]);
+    assert_eq!(
+        capture(|| assert_eq!(recorded_result("7"), Ok(7))),
+        vec![ret("7")]
+    );
+    let captured = capture(|| assert!(recorded_result("x").is_err()));
+    assert_eq!(captured.len(), 1);
+    assert_eq!(captured[0].0, "error");
+    assert_eq!(
+        capture(|| assert_eq!(block_on(recorded_async(1)), 2)),
+        vec![ret("2")]
+    );
+}
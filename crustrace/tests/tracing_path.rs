@@ -0,0 +1,41 @@
+use crustrace::{instrument, omni};
+
+/// A local item named `tracing` must not be picked up by the generated code
+#[allow(dead_code)]
+mod tracing {}
+
+mod my_reexports {
+    pub use crustrace::tracing;
+}
+
+#[instrument(ret, err, fields(pending))]
+fn shadowed(input: &str) -> Result<u32, core::num::ParseIntError> {
+    input.parse()
+}
+
+#[instrument(crate = my_reexports::tracing, level = "debug", ret)]
+fn reexported(x: u32) -> u32 {
+    x * 2
+}
+
+#[omni(crate = ::crustrace::tracing)]
+mod omni_module {
+    pub fn doubled(x: u32) -> u32 {
+        x * 2
+    }
+}
+
+#[instrument]
+async fn shadowed_async(x: u32) -> u32 {
+    x + 1
+}
+
+#[test]
+fn generated_code_ignores_local_tracing_item() {
+    assert_eq!(shadowed("21"), Ok(21));
+    assert!(shadowed("x").is_err());
+    assert_eq!(reexported(21), 42);
+    assert_eq!(omni_module::doubled(21), 42);
+    // The async expansion only needs to compile here
+    drop(shadowed_async(1));
+}
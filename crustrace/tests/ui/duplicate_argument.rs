@@ -0,0 +1,8 @@
+use crustrace::instrument;
+
+#[instrument(ret, level = "debug", ret(Display))]
+fn my_function() -> u32 {
+    42
+}
+
+fn main() {}
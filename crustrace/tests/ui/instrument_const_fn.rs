@@ -0,0 +1,8 @@
+use crustrace::instrument;
+
+#[instrument]
+pub const fn double(x: u32) -> u32 {
+    x * 2
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use crustrace::instrument;
+
+#[instrument]
+fn open(#[record(Pretty)] path: std::path::PathBuf) -> bool {
+    path.exists()
+}
+
+fn main() {}
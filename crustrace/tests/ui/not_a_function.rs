@@ -0,0 +1,6 @@
+use crustrace::instrument;
+
+#[instrument]
+struct NotAFunction;
+
+fn main() {}
@@ -0,0 +1,6 @@
+use crustrace::instrument;
+
+#[instrument("debug")]
+fn my_function() {}
+
+fn main() {}
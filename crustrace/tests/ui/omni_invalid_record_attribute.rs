@@ -0,0 +1,10 @@
+use crustrace::omni;
+
+#[omni]
+mod files {
+    pub fn open(#[record(Pretty)] path: std::path::PathBuf) -> bool {
+        path.exists()
+    }
+}
+
+fn main() {}
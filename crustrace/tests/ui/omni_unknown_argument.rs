@@ -0,0 +1,8 @@
+use crustrace::omni;
+
+#[omni(lvl = "debug")]
+mod my_module {
+    pub fn my_function() {}
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use crustrace::instrument;
+
+#[instrument(skip(password), skip_all)]
+fn login(user: &str, password: &str) -> bool {
+    user.len() < password.len()
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use crustrace::instrument;
+
+#[instrument(lvl = "debug")]
+fn my_function() {}
+
+fn main() {}
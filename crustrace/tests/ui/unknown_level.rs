@@ -0,0 +1,11 @@
+use crustrace::instrument;
+
+#[instrument(level = "warning")]
+fn my_function() {}
+
+#[instrument(ret(level = tracing::Level::WARNING))]
+fn my_other_function() -> u32 {
+    42
+}
+
+fn main() {}
@@ -33,6 +33,11 @@ pub fn hello(target: &str) -> String {
     format!("Hello {}", target)
 }
 
+#[instrument(ret, err)]
+pub fn parse_number(input: &str) -> Result<u32, core::num::ParseIntError> {
+    input.parse()
+}
+
 fn main() {
     // Initialize tracing subscriber to see the spans
     tracing_subscriber::fmt()
@@ -60,4 +65,8 @@ fn main() {
     println!("\n4. Return value:");
     let greeting = hello("world");
     println!(r#"hello("world") = {}"#, greeting);
+
+    println!("\n5. Error value:");
+    let parsed = parse_number("forty-two");
+    println!(r#"parse_number("forty-two") = {:?}"#, parsed);
 }
@@ -23,3 +23,8 @@ pub fn add_numbers(x: i32, y: i32) -> i32 {
 pub fn hello(target: &str) -> String {
     format!("Hello {}", target)
 }
+
+#[instrument(ret, err)]
+pub fn parse_number(input: &str) -> Result<u32, core::num::ParseIntError> {
+    input.parse()
+}
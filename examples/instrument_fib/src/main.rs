@@ -27,4 +27,8 @@ fn main() {
     println!("\n4. Return value:");
     let greeting = hello("world");
     println!(r#"hello("world") = {}"#, greeting);
+
+    println!("\n5. Error value:");
+    let parsed = parse_number("forty-two");
+    println!(r#"parse_number("forty-two") = {:?}"#, parsed);
 }
@@ -0,0 +1,25 @@
+//! Instrumented functions in a crate that depends on `crustrace` under another name, and on
+//! `crustrace-attributes` without `crustrace` at all.
+
+use std::path::PathBuf;
+
+/// Neither `tracing::Value` nor `Debug`
+pub struct Handle;
+
+/// Through the renamed `crustrace`'s re-export of `tracing`
+#[ct::instrument(crate = ct::tracing)]
+pub fn renamed(id: u64, path: &PathBuf, handle: &Handle) -> u64 {
+    id
+}
+
+/// Through `crustrace-attributes`, with this crate's own `tracing`
+#[crustrace_attributes::instrument(crate = ::tracing)]
+pub fn attributes_only(id: u64, path: &PathBuf, handle: &Handle) -> u64 {
+    id
+}
+
+/// Every function in the module, through the renamed `crustrace`
+#[ct::omni(crate = ct::tracing)]
+pub mod renamed_module {
+    pub fn close(handle: super::Handle, retries: Option<u32>) {}
+}
@@ -0,0 +1,50 @@
+use renamed_crate::{attributes_only, renamed, renamed_module, Handle};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+/// Collects `name=value` for every field recorded on a new span
+#[derive(Clone, Default)]
+struct Fields(Arc<Mutex<Vec<String>>>);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.lock().unwrap().push(format!("{field}={value:?}"));
+    }
+}
+
+impl<S: Subscriber> Layer<S> for Fields {
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        attrs.record(&mut self.clone());
+    }
+}
+
+fn recorded(f: impl FnOnce()) -> Vec<String> {
+    let fields = Fields::default();
+    let subscriber = tracing_subscriber::registry().with(fields.clone());
+    tracing::subscriber::with_default(subscriber, f);
+    let recorded = fields.0.lock().unwrap().clone();
+    recorded
+}
+
+#[test]
+fn records_every_parameter_without_a_crustrace_dependency() {
+    let path = PathBuf::from("/tmp");
+    let expected = ["id=1", "path=\"/tmp\"", "handle=\"&renamed_crate::Handle\""];
+    assert_eq!(
+        recorded(|| assert_eq!(renamed(1, &path, &Handle), 1)),
+        expected
+    );
+    assert_eq!(
+        recorded(|| assert_eq!(attributes_only(1, &path, &Handle), 1)),
+        expected
+    );
+    assert_eq!(
+        recorded(|| renamed_module::close(Handle, Some(3))),
+        ["handle=\"renamed_crate::Handle\"", "retries=3"]
+    );
+}